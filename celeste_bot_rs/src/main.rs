@@ -11,7 +11,6 @@
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fmt::Write;
-use std::io::Read;
 use std::sync::Arc;
 
 use serenity::async_trait;
@@ -40,6 +39,7 @@ use serenity::prelude::*;
 use serenity::utils::{content_safe, ContentSafeOptions};
 use tokio::sync::Mutex;
 use serenity::futures::StreamExt;
+use serenity::collector::ComponentInteractionCollectorBuilder;
 
 // A container type is created for inserting into the Client's `data`, which
 // allows for data to be accessible across all events and framework commands, or
@@ -146,7 +146,11 @@ struct General;
 
 
 use celeste_save_data_rs::save_data::SaveData;
-use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::modsave::{ SaveFileKind, load_save_files };
+use celeste_save_data_rs::map_data::{ GameData, LevelData, to_yaml };
+use celeste_save_data_rs::dialog::Dialog;
+use celeste_save_data_rs::mod_zip::ModInfo;
+use celeste_save_data_rs::progress::ProgressView;
 use celeste_visualizer::generate_png;
 use celeste_savefile_db::*;
 use celeste_visualizer::diff::generate_diff_png;
@@ -259,11 +263,19 @@ async fn main() {
 
     {
         let mut data = client.data.write().await;
-        let yml = std::fs::read_to_string("../maps.yaml").unwrap();
-        let game_data = GameData::from_str(&yml).unwrap();
+        // a maps.yaml file or a directory of them
+        let maps_path = env::var("CELESTE_MAPS").unwrap_or_else(|_| "../maps.yaml".to_string());
+        let game_data = GameData::from_path(&maps_path).unwrap();
+        for lint in game_data.lint() {
+            eprintln!("maps.yaml: {}", lint);
+        }
         data.insert::<GameDataStore>(Arc::new(RwLock::new(game_data)));
 
         let db = CelesteSavefileDB::new().await.unwrap();
+        let migrated = db.migrate_kinds().await.unwrap();
+        if migrated > 0 {
+            eprintln!("stored the kind of {} savefiles", migrated);
+        }
         data.insert::<CelesteDBStore>(Arc::new(RwLock::new(db)));
     }
 
@@ -274,15 +286,34 @@ async fn main() {
 
 async fn update_maps_yaml(ctx: &Context, msg: &Message) -> CommandResult {
     let mut data = ctx.data.write().await;
-    let url = "https://raw.githubusercontent.com/niuez/celeste_data/main/maps.yaml";
-    let body = reqwest::get(url)
-        .await?
-        .text()
-        .await?;
-    eprintln!("{}", body);
-    let game_data = GameData::from_str(&body).unwrap();
+    // CELESTE_MAPS (a maps.yaml or a directory with its *.override.yaml) is read again as at startup,
+    // without it maps.yaml is downloaded from the repository
+    let game_data = match env::var("CELESTE_MAPS") {
+        Ok(maps_path) => {
+            GameData::from_path(&maps_path)
+                .map_err(|e| format!("cant load {}: {}", maps_path, e))?
+        }
+        Err(_) => {
+            let url = "https://raw.githubusercontent.com/niuez/celeste_data/main/maps.yaml";
+            let body = reqwest::get(url)
+                .await?
+                .text()
+                .await?;
+            eprintln!("{}", body);
+            GameData::from_str(&body)
+                .map_err(|e| format!("cant parse maps.yaml: {}", e))?
+        }
+    };
+    let lints = game_data.lint();
     data.insert::<GameDataStore>(Arc::new(RwLock::new(game_data)));
-    msg.channel_id.say(&ctx.http, format!("update complete")).await?;
+    let mut content = "update complete\n".to_string();
+    for lint in lints.iter().take(MAX_WARNINGS) {
+        writeln!(content, "- {}", lint)?;
+    }
+    if lints.len() > MAX_WARNINGS {
+        writeln!(content, "... and {} more", lints.len() - MAX_WARNINGS)?;
+    }
+    msg.channel_id.say(&ctx.http, content).await?;
     Ok(())
 }
 
@@ -294,7 +325,20 @@ async fn fetch_maps(ctx: &Context, msg: &Message) -> CommandResult {
 }
 
 
-async fn check_save_data(msg: &Message) -> Result<HashMap<String, (SaveData, Savefile)>, String> {
+// merge every savefile, with modsave files attached to the save of their slot
+fn merge_savefiles<'a, I>(savefiles: I) -> Result<SaveData, String>
+    where I: IntoIterator<Item=&'a Savefile>,
+{
+    let slots = load_save_files(savefiles.into_iter().map(|sf| (sf.filename.as_str(), sf.content.as_str())))
+        .map_err(|e| e.to_string())?;
+    let mut save_data = SaveData::new();
+    for (_, data) in slots {
+        save_data.merge(data);
+    }
+    Ok(save_data)
+}
+
+async fn check_save_data(msg: &Message) -> Result<HashMap<String, Savefile>, String> {
     let mut map = HashMap::new();
     for attachment in msg.attachments.iter() {
         match attachment.download().await {
@@ -302,14 +346,30 @@ async fn check_save_data(msg: &Message) -> Result<HashMap<String, (SaveData, Sav
                 Err(format!("download error {:?}", why))?
             }
             Ok(data) => {
-                let xml = String::from_utf8(data).map_err(|e| format!("from_utf8 error {:?}", e))?;
-                let now_data = SaveData::from_str(&xml)?;
+                let content = String::from_utf8(data).map_err(|e| format!("from_utf8 error {:?}", e))?;
+                // n.celeste is xml, n-modsave-*.celeste is yaml
+                let kind = match SaveFileKind::from_filename(&attachment.filename) {
+                    SaveFileKind::Save { .. } => {
+                        SaveData::from_str(&content)
+                            .map_err(|e| format!("{}: {}", attachment.filename, e))?;
+                        SavefileKind::Save
+                    }
+                    SaveFileKind::ModSave { module, .. } => {
+                        SaveData::new().attach_mod_save(&module, &content)
+                            .map_err(|e| format!("{}: {}", attachment.filename, e))?;
+                        SavefileKind::ModSave
+                    }
+                    SaveFileKind::Settings => {
+                        Err(format!("{}: this is a settings file, not a save file", attachment.filename))?
+                    }
+                };
                 let savefile = Savefile {
                     discord_id: msg.author.id.to_string(),
                     filename: attachment.filename.clone(),
-                    xml,
+                    kind,
+                    content,
                 };
-                map.insert(attachment.filename.clone(), (now_data, savefile));
+                map.insert(attachment.filename.clone(), savefile);
             }
         }
     }
@@ -326,52 +386,53 @@ async fn load_data_dialog(ctx: &Context, msg: &Message, save_data: SaveData) ->
     //table.push(("Chapter", "TotalStrawberries", "Completed", "SingleRunCompleted", "FullClear", "Deaths", "TimePlayed", "BestTime", "BestFullClearTime", "BestDashes", "BestDeaths", "HeartGem"));
     table.push(vec!["Chapter".to_string(), "BestTime".to_string(), "Best/Deaths".to_string(), "Strawberries".to_string()]);
     let sides = vec!["A", "B", "C"];
-    let mut m = {
-        let levels = {
+    let messages = {
+        // one select menu per group, small groups share a menu
+        let menus = {
             let data_read = ctx.data.read().await;
             let game_data_lock = data_read.get::<GameDataStore>()
                 .expect("Expect GameDataStore in TypeMap").clone();
             let game_data = game_data_lock.read().await;
-            game_data.levels().map(|s| (s.level.to_string(), s.name.to_string())).collect::<Vec<_>>()
-        };
-
-        msg.channel_id.send_message(&ctx, move |m| {
-            m.content("select").components(|c| {
-                c.create_action_row(|row| {
-                    row.create_select_menu(|menu| {
-                        menu.custom_id("lang_select");
-                        menu.placeholder("lang");
-                        menu.options(|f| {
-                            f.create_option(|o| o.label("en").value("en").default_selection(true));
-                            f.create_option(|o| o.label("ja").value("ja"))
-                        })
-                    })
-                });
-                for i in 0..4 {
-                    if i * 25 < levels.len() {
-                        c.create_action_row(|row| {
-                            row.create_select_menu(|menu| {
-                                menu.custom_id(format!("level_select{}", i));
-                                menu.placeholder("level");
-                                menu.options(|f| {
-                                    for j in 0..25 {
-                                        if i * 25 + j < levels.len() {
-                                            f.create_option(|o| o.label(levels[i * 25 + j].1.to_string()).value(levels[i * 25 + j].0.to_string()));
-                                        }
-                                    }
-                                    f
-                                })
-                            })
-                        });
+            // e.g. "14/20 clears, 62% berries" under each level set
+            let progress = ProgressView::new(&game_data, &save_data).summaries().into_iter()
+                .map(|(s, progress)| (s.level.as_str(), progress.to_string()))
+                .collect::<HashMap<_, _>>();
+            let mut menus: Vec<(Vec<String>, Vec<(String, String, String)>)> = Vec::new();
+            for (group, levels) in game_data.groups() {
+                let options = levels.iter().map(|s| {
+                    let progress = progress.get(s.level.as_str()).cloned().unwrap_or_else(|| "-".to_string());
+                    let description = match &s.difficulty {
+                        Some(difficulty) => format!("{} / {}", difficulty, progress),
+                        None => progress,
+                    };
+                    (s.level.to_string(), s.name.to_string(), description)
+                }).collect::<Vec<_>>();
+                for chunk in options.chunks(25) {
+                    match menus.last_mut() {
+                        Some((groups, opts)) if opts.len() + chunk.len() <= 25 => {
+                            groups.push(group.to_string());
+                            opts.extend(chunk.iter().cloned());
+                        }
+                        _ => menus.push((vec![group.to_string()], chunk.to_vec())),
                     }
                 }
-                c
-            })
-        })
+            }
+            menus
+        };
+
+        // a message holds 5 action rows, the first one also has the lang menu, so the rest go to more messages
+        let first = std::cmp::min(menus.len(), 4);
+        let mut messages = vec![send_level_menus(ctx, msg, "select", true, 0, &menus[..first]).await?];
+        for (k, chunk) in menus[first..].chunks(5).enumerate() {
+            messages.push(send_level_menus(ctx, msg, "more", false, first + k * 5, chunk).await?);
+        }
+        messages
     };
-    let m = m.await.unwrap();
-    let mut interaction_stream = m.await_component_interactions(&ctx)
+    let message_ids = messages.iter().map(|m| m.id).collect::<HashSet<_>>();
+    let mut interaction_stream = ComponentInteractionCollectorBuilder::new(&ctx)
+        .channel_id(msg.channel_id)
         .author_id(msg.author.id)
+        .filter(move |interaction| message_ids.contains(&interaction.message.id))
         .timeout(std::time::Duration::from_secs(30))
         .build();
     {
@@ -388,7 +449,7 @@ async fn load_data_dialog(ctx: &Context, msg: &Message, save_data: SaveData) ->
                     let game_data = game_data_lock.read().await;
                     eprintln!("{}", selected_level);
                     eprintln!("{:?}", game_data);
-                    generate_png(&save_data, game_data.get_level_data(&selected_level).unwrap().maps(), png_file.path(), &selected_lang)
+                    generate_png(&save_data, ProgressView::new(&game_data, &save_data).level(&selected_level).maps(), png_file.path(), &selected_lang)
                         .map_err(|e| format!("cant generate png {:?}", e))?;
                 }
                 let tokio_file = tokio::fs::File::open(png_file.path()).await
@@ -412,11 +473,50 @@ async fn load_data_dialog(ctx: &Context, msg: &Message, save_data: SaveData) ->
                 }).await?;
             }
         }
-        m.delete(&ctx).await?;
+        for m in messages {
+            m.delete(&ctx).await?;
+        }
         return Ok(());
     };
 }
 
+// menus[i] gets the custom id level_select{offset + i}
+async fn send_level_menus(ctx: &Context, msg: &Message, content: &str, with_lang: bool, offset: usize, menus: &[(Vec<String>, Vec<(String, String, String)>)]) -> Result<Message, String> {
+    msg.channel_id.send_message(&ctx, |m| {
+        m.content(content).components(|c| {
+            if with_lang {
+                c.create_action_row(|row| {
+                    row.create_select_menu(|menu| {
+                        menu.custom_id("lang_select");
+                        menu.placeholder("lang");
+                        menu.options(|f| {
+                            f.create_option(|o| o.label("en").value("en").default_selection(true));
+                            f.create_option(|o| o.label("ja").value("ja"));
+                            f.create_option(|o| o.label("zh").value("zh"));
+                            f.create_option(|o| o.label("ko").value("ko"))
+                        })
+                    })
+                });
+            }
+            for (i, (groups, levels)) in menus.iter().enumerate() {
+                c.create_action_row(|row| {
+                    row.create_select_menu(|menu| {
+                        menu.custom_id(format!("level_select{}", offset + i));
+                        menu.placeholder(groups.join(", ").chars().take(150).collect::<String>());
+                        menu.options(|f| {
+                            for (level, name, description) in levels.iter() {
+                                f.create_option(|o| o.label(name).value(level).description(description));
+                            }
+                            f
+                        })
+                    })
+                });
+            }
+            c
+        })
+    }).await.map_err(|e| format!("cant send menus {:?}", e))
+}
+
 #[command]
 async fn load(ctx: &Context, msg: &Message) -> CommandResult {
     let discord_id = msg.author.id.to_string();
@@ -430,16 +530,15 @@ async fn load(ctx: &Context, msg: &Message) -> CommandResult {
         let db = db_lock.read().await;
         let savefiles = db.get_savefiles(&discord_id).await
             .map_err(|e| format!("cant get data from db {:?}", e))?;
-        let mut save_data = SaveData::new();
-        for sf in savefiles {
-            save_data.merge(SaveData::from_str(&sf.xml)?);
-        }
-        save_data
+        merge_savefiles(&savefiles)?
     };
     load_data_dialog(ctx, msg, save_data).await?;
     Ok(())
 }
 
+// keep the ~update message under discord's length limit
+const MAX_WARNINGS: usize = 10;
+
 #[command]
 async fn update(ctx: &Context, msg: &Message) -> CommandResult {
     let discord_id = msg.author.id.to_string();
@@ -448,6 +547,33 @@ async fn update(ctx: &Context, msg: &Message) -> CommandResult {
             msg.channel_id.say(&ctx.http, why).await?;
         }
         Ok(new_savefiles) => {
+            // let the user look at anything suspicious before applying
+            let warnings = {
+                let data_read = ctx.data.read().await;
+                let game_data_lock = data_read.get::<GameDataStore>()
+                    .expect("Expect GameDataStore in TypeMap").clone();
+                let game_data = game_data_lock.read().await;
+                let mut warnings = Vec::new();
+                for sf in new_savefiles.values() {
+                    if sf.kind == SavefileKind::Save {
+                        for warning in SaveData::from_str(&sf.content)?.validate(&game_data) {
+                            warnings.push(format!("{}: {}", sf.filename, warning));
+                        }
+                    }
+                }
+                warnings
+            };
+            let mut content = String::new();
+            if !warnings.is_empty() {
+                writeln!(content, "warnings:")?;
+                for warning in warnings.iter().take(MAX_WARNINGS) {
+                    writeln!(content, "- {}", warning)?;
+                }
+                if warnings.len() > MAX_WARNINGS {
+                    writeln!(content, "... and {} more", warnings.len() - MAX_WARNINGS)?;
+                }
+            }
+            content.push_str("select");
             let png_diff_file = tempfile::NamedTempFile::new().map_err(|e| format!("cant create tempfile {:?}", e))?;
             {
                 let data_read = ctx.data.read().await;
@@ -460,18 +586,10 @@ async fn update(ctx: &Context, msg: &Message) -> CommandResult {
                 let now_savefiles = db.get_savefiles(&discord_id).await
                     .map_err(|e| format!("cant get data from db {:?}", e))?;
 
-                let mut before = SaveData::new();
-                let mut after = SaveData::new();
-                for sf in now_savefiles {
-                    let data = SaveData::from_str(&sf.xml)?;
-                    if !new_savefiles.contains_key(&sf.filename) {
-                        after.merge(data.clone());
-                    }
-                    before.merge(data);
-                }
-                for e in new_savefiles.values() {
-                    after.merge(e.0.clone())
-                }
+                let before = merge_savefiles(&now_savefiles)?;
+                let after = merge_savefiles(now_savefiles.iter()
+                    .filter(|sf| !new_savefiles.contains_key(&sf.filename))
+                    .chain(new_savefiles.values()))?;
                 generate_diff_png(&game_data, &before, &after, png_diff_file.path(), "en")?;
             };
             let tokio_diff_file = tokio::fs::File::open(png_diff_file.path()).await
@@ -482,7 +600,7 @@ async fn update(ctx: &Context, msg: &Message) -> CommandResult {
                         file: &tokio_diff_file,
                         filename: format!("{}_diff.png", msg.author),
                     });
-                    m.content("select").components(|c| {
+                    m.content(&content).components(|c| {
                         c.create_action_row(|row| {
                             row.create_button(|b| {
                                 b.custom_id("apply");
@@ -520,7 +638,7 @@ async fn update(ctx: &Context, msg: &Message) -> CommandResult {
                     let db_lock = data_read.get::<CelesteDBStore>()
                         .expect("Expect CelesteDBStore in TypeMap").clone();
                     let db = db_lock.read().await;
-                    for (filename, (_, sf)) in new_savefiles.into_iter() {
+                    for (filename, sf) in new_savefiles.into_iter() {
                         db.update_savefile(sf).await
                             .map_err(|e| format!("cant save {:?}", e))?;
                     }
@@ -551,12 +669,23 @@ async fn rescue(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
         let now_savefiles = db.get_savefiles(&discord_id).await
             .map_err(|e| format!("cant get data from db {:?}", e))?;
 
-        for sf in now_savefiles {
+        // `~rescue merged` hands back all the files as one save
+        if args.single::<String>().map(|a| a == "merged").unwrap_or(false) {
+            let save_data = merge_savefiles(&now_savefiles)?;
             let ans_file = tempfile::NamedTempFile::new().map_err(|e| format!("cant create tempfile {:?}", e))?;
             let mut tokio_file = tokio::fs::File::create(ans_file.path()).await
                 .map_err(|e| format!("cant create tokio file {:?}", e))?;
-            tokio_file.write_all(sf.xml.as_bytes()).await?;
-            ans_files.push((sf.filename, ans_file));
+            tokio_file.write_all(save_data.to_xml().as_bytes()).await?;
+            ans_files.push(("merged.celeste".to_string(), ans_file));
+        }
+        else {
+            for sf in now_savefiles {
+                let ans_file = tempfile::NamedTempFile::new().map_err(|e| format!("cant create tempfile {:?}", e))?;
+                let mut tokio_file = tokio::fs::File::create(ans_file.path()).await
+                    .map_err(|e| format!("cant create tokio file {:?}", e))?;
+                tokio_file.write_all(sf.content.as_bytes()).await?;
+                ans_files.push((sf.filename, ans_file));
+            }
         }
     }
     {
@@ -607,36 +736,21 @@ async fn unknown(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
         let db = db_lock.read().await;
         let now_savefiles = db.get_savefiles(&discord_id).await
             .map_err(|e| format!("cant get data from db {:?}", e))?;
-        let mut savedata = SaveData::new();
-        for sf in now_savefiles {
-            savedata.merge(SaveData::from_str(&sf.xml)?);
-        }
+        let savedata = merge_savefiles(&now_savefiles)?;
         let savedata = savedata;
-        let known_levels: HashSet<_> = game_data.levels().map(|s| s.level.clone()).collect();
-        let mut unknown_levels = HashSet::new();
-        for level in savedata.levels.keys() {
-            if !known_levels.contains(level) {
-                unknown_levels.insert(level.clone());
-            }
-        }
+        let unknown_levels = game_data.unknown_levels(&savedata);
         if let Ok(level) = args.single::<String>() {
             if !unknown_levels.contains(&level) {
                 msg.channel_id.say(&ctx.http, "not found").await?;
             }
             else {
+                let yaml = to_yaml(&[LevelData::template(&level, &savedata, |_| None)])
+                    .map_err(|e| format!("cant write yaml {}", e))?;
                 let ans_file = tempfile::NamedTempFile::new().map_err(|e| format!("cant create tempfile {:?}", e))?;
                 {
                     let mut tokio_file = tokio::fs::File::create(ans_file.path()).await
                         .map_err(|e| format!("cant create tokio file {:?}", e))?;
-                    tokio_file.write(format!("- level: {}\n  name: ''\n", level).as_bytes()).await?;
-                    for map_code in savedata.levels[&level].iter() {
-                        if map_code.side == 0 {
-                            tokio_file.write(format!("    - sid: '{}'\n", map_code.sid).as_bytes()).await?;
-                            tokio_file.write(format!("      name:\n")                  .as_bytes()).await?;
-                            tokio_file.write(format!("        en: ''\n")               .as_bytes()).await?;
-                            tokio_file.write(format!("      sides: [0]\n")             .as_bytes()).await?;
-                        }
-                    }
+                    tokio_file.write_all(yaml.as_bytes()).await?;
                 }
                 {
                     let tokio_file = tokio::fs::File::open(ans_file.path()).await?;
@@ -650,9 +764,7 @@ async fn unknown(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
             }
         }
         else {
-            let mut levels = unknown_levels.into_iter().collect::<Vec<_>>();
-            levels.sort();
-            let message = levels.join("\n");
+            let message = unknown_levels.join("\n");
             msg.channel_id.say(&ctx.http, message).await?;
         }
     }
@@ -673,19 +785,9 @@ async fn find_english(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
         let db = db_lock.read().await;
         let now_savefiles = db.get_savefiles(&discord_id).await
             .map_err(|e| format!("cant get data from db {:?}", e))?;
-        let mut savedata = SaveData::new();
-        for sf in now_savefiles {
-            savedata.merge(SaveData::from_str(&sf.xml)?);
-        }
+        let savedata = merge_savefiles(&now_savefiles)?;
         let savedata = savedata;
-        let known_levels: HashSet<_> = game_data.levels().map(|s| s.level.clone()).collect();
-        let mut unknown_levels = HashSet::new();
-        for level in savedata.levels.keys() {
-            if !known_levels.contains(level) {
-                unknown_levels.insert(level.clone());
-            }
-        }
-        let mut english_dist = HashMap::new();
+        let mut dialog = Dialog::default();
         for attachment in msg.attachments.iter() {
             match attachment.download().await {
                 Err(why) => {
@@ -693,61 +795,22 @@ async fn find_english(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
                 }
                 Ok(data) => {
                     let english = String::from_utf8(data).map_err(|e| format!("from_utf8 error {:?}", e))?;
-                    let english = english.replace("=", "= ");
-                    let splits = english.lines()
-                        .filter(|s| !s.strip_prefix("#").is_some())
-                        .map(|s| s.split_whitespace().collect::<Vec<_>>())
-                        .flatten()
-                        .rev()
-                        .collect::<Vec<_>>();
-                    let mut now: Vec<String> = vec![];
-                    for s in splits {
-                        if let Some(key) = s.strip_suffix("=") {
-                            now.reverse();
-                            english_dist.insert(key.to_uppercase().to_string(), now.join(" "));
-                            eprintln!("{} {}", key.to_string(), now.join(" "));
-                            now.clear();
-                        }
-                        else {
-                            now.push(s.to_string());
-                        }
-                    }
+                    dialog.merge(Dialog::parse(&english));
                 }
             }
         }
 
+        // only the level sets whose maps all have a name in the dialog
+        let templates = game_data.templates(&savedata, |sid| dialog.map_name(sid))
+            .into_iter()
+            .filter(|level| level.is_named())
+            .collect::<Vec<_>>();
+        let yaml = to_yaml(&templates).map_err(|e| format!("cant write yaml {}", e))?;
         let ans_file = tempfile::NamedTempFile::new().map_err(|e| format!("cant create tempfile {:?}", e))?;
         {
             let mut tokio_file = tokio::fs::File::create(ans_file.path()).await
                 .map_err(|e| format!("cant create tokio file {:?}", e))?;
-            for unknown_level in unknown_levels {
-                let mut ok = true;
-                for map_code in savedata.levels[&unknown_level].iter() {
-                    let key = map_code.sid.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>().to_uppercase();
-                    eprintln!("{} -> {}", map_code.sid, key);
-                    if let Some(value) = english_dist.get(key.as_str()) {
-                    }
-                    else {
-                        eprintln!("{}: cant find {}", unknown_level, key);
-                        ok = false;
-                    }
-                }
-                if ok {
-                    eprintln!("find! {}", unknown_level);
-                    {
-                        tokio_file.write(format!("- level: {}\n  name: '{}'\n  maps:\n", unknown_level, unknown_level).as_bytes()).await?;
-                        for map_code in savedata.levels[&unknown_level].iter() {
-                            let key = map_code.sid.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>().to_uppercase();
-                            if map_code.side == 0 {
-                                tokio_file.write(format!("    - sid: '{}'\n", map_code.sid).as_bytes()).await?;
-                                tokio_file.write(format!("      name:\n")                  .as_bytes()).await?;
-                                tokio_file.write(format!("        en: '{}'\n", english_dist[key.as_str()])               .as_bytes()).await?;
-                                tokio_file.write(format!("      sides: [0]\n")             .as_bytes()).await?;
-                            }
-                        }
-                    }
-                }
-            }
+            tokio_file.write_all(yaml.as_bytes()).await?;
         }
 
         {
@@ -768,30 +831,12 @@ async fn find_english(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
 
 #[command]
 async fn find_zip(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let discord_id = msg.author.id.to_string();
     {
         let data_read = ctx.data.read().await;
         let game_data_lock = data_read.get::<GameDataStore>()
             .expect("Expect GameDataStore in TypeMap").clone();
         let game_data = game_data_lock.read().await;
-        let db_lock = data_read.get::<CelesteDBStore>()
-            .expect("Expect CelesteDBStore in TypeMap").clone();
-        let db = db_lock.read().await;
-        let now_savefiles = db.get_savefiles(&discord_id).await
-            .map_err(|e| format!("cant get data from db {:?}", e))?;
-        let mut savedata = SaveData::new();
-        for sf in now_savefiles {
-            savedata.merge(SaveData::from_str(&sf.xml)?);
-        }
-        let savedata = savedata;
-        let known_levels: HashSet<_> = game_data.levels().map(|s| s.level.clone()).collect();
-        let mut unknown_levels = HashSet::new();
-        for level in savedata.levels.keys() {
-            if !known_levels.contains(level) {
-                unknown_levels.insert(level.clone());
-            }
-        }
-        let mut english_dist = HashMap::new();
+        let mut templates: Vec<LevelData> = Vec::new();
 
         if let Ok(urls) = args.single::<String>() {
             for url in urls.split_whitespace() {
@@ -804,65 +849,22 @@ async fn find_zip(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
                 msg.channel_id.say(&ctx.http, format!("Downloaded!")).await?;
                 let mut cur = std::io::Cursor::new(bytes);
                 std::io::copy(&mut cur, &mut zip_file)?;
-                let mut zip = zip::ZipArchive::new(zip_file)?;
-                if let Ok(mut file) = zip.by_name("Dialog/English.txt") {
-                    let mut english = String::new();
-                    file.read_to_string(&mut english)?;
-                    let english = english.replace("=", "= ");
-                    let splits = english.lines()
-                        .filter(|s| !s.strip_prefix("#").is_some())
-                        .map(|s| s.split_whitespace().collect::<Vec<_>>())
-                        .flatten()
-                        .rev()
-                        .collect::<Vec<_>>();
-                    let mut now: Vec<String> = vec![];
-                    for s in splits {
-                        if let Some(key) = s.strip_suffix("=") {
-                            now.reverse();
-                            english_dist.insert(key.to_uppercase().to_string(), now.join(" "));
-                            eprintln!("{} {}", key.to_string(), now.join(" "));
-                            now.clear();
-                        }
-                        else {
-                            now.push(s.to_string());
-                        }
-                    }
-                };
+                let mod_info = ModInfo::from_zip(zip_file).map_err(|e| format!("{}: {}", url, e))?;
+                // the level sets already in maps.yaml are left as they are
+                let new_levels = mod_info.levels.into_iter()
+                    .filter(|level| game_data.get_level_data(&level.level).is_none())
+                    .collect::<Vec<_>>();
+                msg.channel_id.say(&ctx.http, format!("{} {}: {} new level sets", mod_info.name, mod_info.version, new_levels.len())).await?;
+                templates.extend(new_levels);
             }
         }
 
+        let yaml = to_yaml(&templates).map_err(|e| format!("cant write yaml {}", e))?;
         let ans_file = tempfile::NamedTempFile::new().map_err(|e| format!("cant create tempfile {:?}", e))?;
         {
             let mut tokio_file = tokio::fs::File::create(ans_file.path()).await
                 .map_err(|e| format!("cant create tokio file {:?}", e))?;
-            for unknown_level in unknown_levels {
-                let mut ok = true;
-                for map_code in savedata.levels[&unknown_level].iter() {
-                    let key = map_code.sid.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>().to_uppercase();
-                    eprintln!("{} -> {}", map_code.sid, key);
-                    if let Some(value) = english_dist.get(key.as_str()) {
-                    }
-                    else {
-                        eprintln!("{}: cant find {}", unknown_level, key);
-                        ok = false;
-                    }
-                }
-                if ok {
-                    eprintln!("find! {}", unknown_level);
-                    {
-                        tokio_file.write(format!("- level: {}\n  name: '{}'\n  maps:\n", unknown_level, unknown_level).as_bytes()).await?;
-                        for map_code in savedata.levels[&unknown_level].iter() {
-                            let key = map_code.sid.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>().to_uppercase();
-                            if map_code.side == 0 {
-                                tokio_file.write(format!("    - sid: '{}'\n", map_code.sid).as_bytes()).await?;
-                                tokio_file.write(format!("      name:\n")                  .as_bytes()).await?;
-                                tokio_file.write(format!("        en: '{}'\n", english_dist[key.as_str()])               .as_bytes()).await?;
-                                tokio_file.write(format!("      sides: [0]\n")             .as_bytes()).await?;
-                            }
-                        }
-                    }
-                }
-            }
+            tokio_file.write_all(yaml.as_bytes()).await?;
         }
 
         {
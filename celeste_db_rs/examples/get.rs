@@ -1,5 +1,4 @@
 use celeste_db_rs::CelesteDB;
-use celeste_save_data_rs::save_data::SaveData;
 use celeste_save_data_rs::map_data::GameData;
 
 #[tokio::main]
@@ -5,11 +5,13 @@ use mongodb::options::{ FindOneAndReplaceOptions };
 use celeste_save_data_rs::time::Time;
 use celeste_save_data_rs::save_data::{ SaveData, AreaModeStats, MapCode, Strawberries, EntityID, };
 use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::progress::ProgressView;
 use futures::stream::TryStreamExt;
 
 #[derive(Serialize, Deserialize)]
 struct MapRecord {
     #[serde(rename = "_id", skip_serializing)]
+    #[allow(dead_code)]
     id: Option<ObjectId>,
     discord_id: String,
     level: String,
@@ -31,11 +33,11 @@ struct MapRecord {
 }
 
 impl MapRecord {
-    pub fn from_stats(code: &MapCode, stats: &AreaModeStats, discord_id: &str) -> Self {
+    pub fn from_stats(level: &str, code: &MapCode, stats: &AreaModeStats, discord_id: &str) -> Self {
         Self {
             id: None,
             discord_id: discord_id.to_string(),
-            level: code.level.clone(),
+            level: level.to_string(),
             sid: code.sid.clone(),
             side: code.side as i64,
 
@@ -56,7 +58,6 @@ impl MapRecord {
     pub fn to_code_stats(&self) -> (MapCode, AreaModeStats) {
         (
             MapCode {
-                level: self.level.clone(),
                 sid: self.sid.clone(),
                 side: self.side as usize,
             },
@@ -76,11 +77,15 @@ impl MapRecord {
                         .map(|s| EntityID { key: s.clone() })
                         .collect()
                 },
+                // checkpoints and assists are not stored
+                ..Default::default()
             },
         )
     }
 }
 
+// client and db are kept for queries other than the records
+#[allow(dead_code)]
 pub struct CelesteDB {
     client: Client,
     db: Database,
@@ -104,35 +109,36 @@ impl CelesteDB {
     
     pub async fn update_record(&self, save_data: &SaveData, game_data: &GameData, discord_id: &str) -> Result<(), String> {
         let upsert_option = FindOneAndReplaceOptions::builder().upsert(true).build();
-        for level in game_data.levels() {
-            for map_data in game_data.get_level_data(&level.level).unwrap().maps() {
-                if let Some(stats) = save_data.map_stats.get(&map_data.code) {
-                    let map_record = MapRecord::from_stats(&map_data.code, stats, discord_id);
-                    self.record_col.find_one_and_replace(
-                        doc!{
-                            "discord_id": discord_id.to_string(),
-                            "level": map_data.code.level.clone(),
-                            "sid": map_data.code.sid.clone(),
-                            "side": map_data.code.side as i64,
-                        },
-                        map_record,
-                        upsert_option.clone(),
-                    ).await
-                        .map_err(|e| format!("replace error {:?}", e))?;
+        let view = ProgressView::new(game_data, save_data);
+        for row in view.rows() {
+            if let Some(stats) = row.stats {
+                let map_data = row.map;
+                let map_record = MapRecord::from_stats(&row.level.level, &map_data.code, stats, discord_id);
+                self.record_col.find_one_and_replace(
+                    doc!{
+                        "discord_id": discord_id.to_string(),
+                        "level": row.level.level.clone(),
+                        "sid": map_data.code.sid.clone(),
+                        "side": map_data.code.side as i64,
+                    },
+                    map_record,
+                    upsert_option.clone(),
+                ).await
+                    .map_err(|e| format!("replace error {:?}", e))?;
 
-                }
             }
         }
         Ok(())
     }
 
-    pub async fn get_save_data(&self, game_data: &GameData, discord_id: &str) -> Result<SaveData, String> {
+    pub async fn get_save_data(&self, _game_data: &GameData, discord_id: &str) -> Result<SaveData, String> {
         let mut save_data = SaveData::new();
         let mut cursor = self.record_col.find(doc! { "discord_id": discord_id }, None).await
             .map_err(|e| format!("find error {:?}", e))?;
         while let Some(record) = cursor.try_next().await
             .map_err(|e| format!("try next error {:?}", e))? {
                 let (code, stats) = record.to_code_stats();
+                save_data.levels.entry(record.level.clone()).or_default().insert(code.clone());
                 save_data.map_stats.insert(code, stats);
             }
         Ok(save_data)
@@ -0,0 +1,21 @@
+use celeste_save_data_rs::builder::{ SaveDataBuilder, AreaBuilder, SideBuilder };
+use celeste_save_data_rs::time::Time;
+
+// prints a small .celeste file, e.g. `cargo run --example build > 0.celeste`
+fn main() {
+    let xml = SaveDataBuilder::new()
+        .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new()
+            .cassette()
+            .side(0, SideBuilder::new()
+                .cleared(Time::from_frames(9000), 12)
+                .full_clear(Time::from_frames(15000))
+                .heart_gem()
+                .deaths(40)
+                .berries(["a-01:12", "a-02:4", "b-03:7"])
+                .checkpoints(["6", "9b"]))
+            .side(1, SideBuilder::new().completed().deaths(120)))
+        .area("Celeste", "Celeste/2-OldSite", AreaBuilder::new()
+            .side(0, SideBuilder::new().deaths(8).time_played(Time::from_frames(3000))))
+        .to_xml();
+    println!("{}", xml);
+}
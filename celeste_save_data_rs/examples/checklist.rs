@@ -0,0 +1,15 @@
+use celeste_save_data_rs::save_data::SaveData;
+use celeste_save_data_rs::checklist::Checklist;
+use celeste_save_data_rs::map_data::GameData;
+
+fn main() {
+    let xml = std::fs::read_to_string("0.celeste").unwrap();
+    let save_data = SaveData::from_str(&xml).unwrap();
+    let game_data = GameData::from_path("../maps.yaml").unwrap();
+    let checklist = Checklist::vanilla(&save_data, &game_data);
+    let (done, total) = checklist.progress();
+    println!("{}/{}", done, total);
+    for check in checklist.missing() {
+        println!("{}", check);
+    }
+}
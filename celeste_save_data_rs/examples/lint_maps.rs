@@ -0,0 +1,27 @@
+use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::save_data::SaveData;
+
+// check maps.yaml before a pull request, optionally against some save files
+//   cargo run --example lint_maps -- ../maps.yaml 0.celeste 1.celeste
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "../maps.yaml".to_string());
+    let yml = std::fs::read_to_string(path).unwrap();
+    let game_data = match GameData::from_str(&yml) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let saves = args
+        .map(|path| SaveData::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+        .collect::<Vec<_>>();
+    let lints = if saves.is_empty() { game_data.lint() } else { game_data.lint_with_saves(saves.iter()) };
+    for lint in lints.iter() {
+        println!("{}", lint);
+    }
+    if !lints.is_empty() {
+        std::process::exit(1);
+    }
+}
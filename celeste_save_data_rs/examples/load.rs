@@ -1,7 +1,8 @@
 use celeste_save_data_rs::save_data::*;
 
 fn main() {
-    let xml = std::fs::read_to_string("0.celeste").unwrap();
+    let path = std::env::args().nth(1).unwrap_or_else(|| "0.celeste".to_string());
+    let xml = std::fs::read_to_string(path).unwrap();
     let xml = SaveData::from_str(&xml).unwrap();
     println!("{:#?}", xml);
 }
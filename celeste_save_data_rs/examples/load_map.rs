@@ -1,7 +1,8 @@
 use celeste_save_data_rs::map_data::*;
 
+// a maps.yaml file or a directory such as maps.d
 fn main() {
-    let yml = std::fs::read_to_string("../maps.yaml").unwrap();
-    let yml: GameData = serde_yaml::from_str(&yml).unwrap();
-    println!("{:#?}", yml);
+    let path = std::env::args().nth(1).unwrap_or_else(|| "../maps.yaml".to_string());
+    let game_data = GameData::from_path(path).unwrap();
+    println!("{:#?}", game_data);
 }
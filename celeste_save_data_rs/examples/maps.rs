@@ -1,19 +1,12 @@
 use celeste_save_data_rs::save_data::*;
+use celeste_save_data_rs::map_data::{ GameData, to_yaml };
 
+// maps.yaml entries for the level sets of a save file that are not registered yet
 fn main() {
     let xml = std::fs::read_to_string("0.celeste").unwrap();
     let data = SaveData::from_str(&xml).unwrap();
-    let target_level = "StrawberryJam2021/5-Grandmaster";
-    println!("- level: {}", target_level);
-    println!("  maps:");
-    let mut sorted = data.map_stats.iter().collect::<Vec<_>>();
-    sorted.sort_by_key(|e| e.0.sid.to_lowercase());
-    for (code, _map) in sorted.into_iter() {
-        if code.level == target_level && code.side == 0 {
-            println!("    - sid: '{}'", code.sid);
-            println!("      name:");
-            println!("        en: ''");
-            println!("      sides: [0]");
-        }
-    }
+    let yml = std::fs::read_to_string("../maps.yaml").unwrap();
+    let game_data = GameData::from_str(&yml).unwrap();
+    let templates = game_data.templates(&data, |_| None);
+    print!("{}", to_yaml(&templates).unwrap());
 }
@@ -0,0 +1,24 @@
+use celeste_save_data_rs::save_data::SaveData;
+use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::progress::ProgressView;
+
+// lists the registered strawberries a save file has not collected
+//   cargo run --example missing_berries -- 0.celeste StrawberryJam2021/1-Beginner
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| "0.celeste".to_string());
+    let xml = std::fs::read_to_string(path).unwrap();
+    let save_data = SaveData::from_str(&xml).unwrap();
+    let yml = std::fs::read_to_string("../maps.yaml").unwrap();
+    let game_data = GameData::from_str(&yml).unwrap();
+    let mut view = ProgressView::new(&game_data, &save_data);
+    if let Some(level) = args.next() {
+        view = view.level(&level);
+    }
+    for row in view.rows() {
+        let missing = row.map.missing_berries(row.stats);
+        if !missing.is_empty() {
+            println!("{}: {}", row.name(), missing.join(", "));
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{ HashMap, HashSet };
+use crate::save_data::{ SaveData, AreaModeStats, Strawberries, Checkpoints, EntityID };
+use crate::time::{ Time, Timestamp };
+
+// Make a SaveData in code, e.g. for demo images or to reproduce a bug without someone's file.
+//   let save_data = SaveDataBuilder::new()
+//       .name("Madeline")
+//       .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new()
+//           .cassette()
+//           .side(0, SideBuilder::new().cleared(Time(1_200_000_000), 3).berries(["1:12", "2:4"])))
+//       .build();
+const VANILLA_LEVEL_SET: &str = "Celeste";
+
+// vanilla areas in the order of their IDs, Prologue is 0 and Farewell 10
+const VANILLA_SIDS: [&str; 11] = [
+    "Celeste/0-Intro",
+    "Celeste/1-ForsakenCity",
+    "Celeste/2-OldSite",
+    "Celeste/3-CelestialResort",
+    "Celeste/4-GoldenRidge",
+    "Celeste/5-MirrorTemple",
+    "Celeste/6-Reflection",
+    "Celeste/7-Summit",
+    "Celeste/8-Epilogue",
+    "Celeste/9-Core",
+    "Celeste/LostLevels",
+];
+
+#[derive(Debug, Clone)]
+pub struct SaveDataBuilder {
+    save_data: SaveData,
+    // the next ID of an area in each level set
+    next_ids: HashMap<String, u64>,
+}
+
+impl Default for SaveDataBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveDataBuilder {
+    pub fn new() -> Self {
+        let mut save_data = SaveData::new();
+        save_data.version = "1.4.0.0".to_string();
+        save_data.name = "Madeline".to_string();
+        Self { save_data, next_ids: HashMap::new() }
+    }
+    pub fn version(mut self, version: &str) -> Self {
+        self.save_data.version = version.to_string();
+        self
+    }
+    pub fn name(mut self, name: &str) -> Self {
+        self.save_data.name = name.to_string();
+        self
+    }
+    // the file time, defaults to the time played on all maps
+    pub fn time(mut self, time: Time) -> Self {
+        self.save_data.time = time;
+        self
+    }
+    pub fn last_save(mut self, last_save: Timestamp) -> Self {
+        self.save_data.last_save = last_save;
+        self
+    }
+    pub fn total_deaths(mut self, deaths: u64) -> Self {
+        self.save_data.total_deaths = deaths;
+        self
+    }
+    pub fn assist_mode(mut self) -> Self {
+        self.save_data.assist_mode = true;
+        self
+    }
+    pub fn variant_mode(mut self) -> Self {
+        self.save_data.variant_mode = true;
+        self
+    }
+    pub fn cheat_mode(mut self) -> Self {
+        self.save_data.cheat_mode = true;
+        self
+    }
+    // areas of "Celeste" go to the vanilla Areas, anything else to LevelSets.
+    // Vanilla areas get their own ID, the areas of a mod are numbered from 0 in the order they are added.
+    pub fn area(mut self, level_set: &str, sid: &str, area: AreaBuilder) -> Self {
+        let mut modes = area.modes;
+        if modes.len() < 3 {
+            modes.resize_with(3, Default::default);
+        }
+        let modes = modes.into_iter().map(|mode| mode.unwrap_or_default().build()).collect();
+        let id = self.area_id(level_set, sid);
+        self.save_data.push_area(level_set, id, sid, area.cassette, modes);
+        self
+    }
+    fn area_id(&mut self, level_set: &str, sid: &str) -> u64 {
+        let mut first = 0;
+        if level_set == VANILLA_LEVEL_SET {
+            if let Some(id) = VANILLA_SIDS.iter().position(|s| *s == sid) {
+                return id as u64;
+            }
+            // an unknown SID in Celeste comes after the vanilla areas
+            first = VANILLA_SIDS.len() as u64;
+        }
+        let next = self.next_ids.entry(level_set.to_string()).or_insert(first);
+        let id = *next;
+        *next += 1;
+        id
+    }
+    pub fn build(mut self) -> SaveData {
+        self.save_data.build_map_stats();
+        let map_time = self.save_data.map_stats.values().map(|stats| stats.time_played.0).sum();
+        if self.save_data.time.0 < map_time {
+            self.save_data.time = Time(map_time);
+        }
+        if self.save_data.total_deaths == 0 {
+            self.save_data.total_deaths = self.save_data.map_stats.values().map(|stats| stats.deaths).sum();
+        }
+        self.save_data
+    }
+    // the .celeste file
+    pub fn to_xml(self) -> String {
+        self.build().to_xml()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AreaBuilder {
+    cassette: bool,
+    modes: Vec<Option<SideBuilder>>,
+}
+
+impl AreaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cassette(mut self) -> Self {
+        self.cassette = true;
+        self
+    }
+    // 0 for the A-side, sides not given are left unplayed
+    pub fn side(mut self, side: usize, stats: SideBuilder) -> Self {
+        if self.modes.len() <= side {
+            self.modes.resize_with(side + 1, Default::default);
+        }
+        self.modes[side] = Some(stats);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SideBuilder {
+    stats: AreaModeStats,
+    berries: Vec<String>,
+    checkpoints: Vec<String>,
+}
+
+impl SideBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // completed, but not in one run
+    pub fn completed(mut self) -> Self {
+        self.stats.completed = true;
+        self
+    }
+    // completed in one run with this time and deaths
+    pub fn cleared(mut self, best_time: Time, best_deaths: u64) -> Self {
+        self.stats.completed = true;
+        self.stats.single_run_completed = true;
+        self.stats.best_time = best_time;
+        self.stats.best_deaths = best_deaths;
+        self
+    }
+    pub fn best_dashes(mut self, dashes: u64) -> Self {
+        self.stats.best_dashes = dashes;
+        self
+    }
+    pub fn full_clear(mut self, time: Time) -> Self {
+        self.stats.completed = true;
+        self.stats.full_clear = true;
+        self.stats.best_full_clear_time = time;
+        self
+    }
+    pub fn heart_gem(mut self) -> Self {
+        self.stats.heart_gem = true;
+        self
+    }
+    // total deaths, at least the best deaths
+    pub fn deaths(mut self, deaths: u64) -> Self {
+        self.stats.deaths = deaths;
+        self
+    }
+    // total time, at least the best times
+    pub fn time_played(mut self, time: Time) -> Self {
+        self.stats.time_played = time;
+        self
+    }
+    // EntityID keys such as "a-01:12"
+    pub fn berries<I, S>(mut self, keys: I) -> Self
+        where I: IntoIterator<Item=S>,
+              S: AsRef<str>,
+    {
+        self.berries.extend(keys.into_iter().map(|key| key.as_ref().to_string()));
+        self
+    }
+    // room names of the checkpoints reached
+    pub fn checkpoints<I, S>(mut self, rooms: I) -> Self
+        where I: IntoIterator<Item=S>,
+              S: AsRef<str>,
+    {
+        self.checkpoints.extend(rooms.into_iter().map(|room| room.as_ref().to_string()));
+        self
+    }
+    pub fn build(self) -> AreaModeStats {
+        let mut stats = self.stats;
+        stats.strawberries = Strawberries {
+            entity_id: self.berries.into_iter().map(|key| EntityID { key }).collect(),
+        };
+        stats.total_strawberries = stats.strawberries.entity_id.len() as u64;
+        stats.checkpoints = Checkpoints {
+            rooms: self.checkpoints.into_iter().collect::<HashSet<_>>(),
+        };
+        // keep the file consistent, see SaveData::validate
+        stats.deaths = std::cmp::max(stats.deaths, stats.best_deaths);
+        let best = std::cmp::max(stats.best_time, stats.best_full_clear_time);
+        stats.time_played = std::cmp::max(stats.time_played, best);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_data::MapCode;
+
+    fn code(sid: &str, side: usize) -> MapCode {
+        MapCode { sid: sid.to_string(), side }
+    }
+
+    #[test]
+    fn area_ids() {
+        let save_data = SaveDataBuilder::new()
+            .area("Celeste", "Celeste/2-OldSite", AreaBuilder::new())
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new())
+            .area("Celeste", "Celeste/Custom", AreaBuilder::new())
+            .area("SJ", "SJ/1-Beginner/a", AreaBuilder::new())
+            .area("Other", "Other/x", AreaBuilder::new())
+            .area("SJ", "SJ/1-Beginner/b", AreaBuilder::new())
+            .build();
+        let id = |sid: &str| save_data.area_info[sid].id;
+        assert_eq!(id("Celeste/1-ForsakenCity"), 1);
+        assert_eq!(id("Celeste/2-OldSite"), 2);
+        assert_eq!(id("Celeste/Custom"), 11);
+        assert_eq!(id("SJ/1-Beginner/a"), 0);
+        assert_eq!(id("SJ/1-Beginner/b"), 1);
+        assert_eq!(id("Other/x"), 0);
+        assert_eq!(save_data.area_info["SJ/1-Beginner/b"].level_set, "SJ");
+        // vanilla areas are written in ID order whatever order they were added in
+        let vanilla = save_data.raw_areas().filter(|(level_set, _)| *level_set == "Celeste").map(|(_, sid)| sid).collect::<Vec<_>>();
+        assert_eq!(vanilla, ["Celeste/1-ForsakenCity", "Celeste/2-OldSite", "Celeste/Custom"]);
+    }
+
+    #[test]
+    fn sides() {
+        let save_data = SaveDataBuilder::new()
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new()
+                .cassette()
+                .side(1, SideBuilder::new().cleared(Time(100), 3).heart_gem()))
+            .build();
+        // A to C are always there
+        for side in 0..3 {
+            assert!(save_data.map_stats.contains_key(&code("Celeste/1-ForsakenCity", side)));
+        }
+        assert!(!save_data.map_stats[&code("Celeste/1-ForsakenCity", 0)].completed);
+        let b_side = &save_data.map_stats[&code("Celeste/1-ForsakenCity", 1)];
+        assert!(b_side.single_run_completed && b_side.heart_gem);
+        assert!(save_data.area_info["Celeste/1-ForsakenCity"].cassette);
+    }
+
+    #[test]
+    fn side_stats_stay_consistent() {
+        let stats = SideBuilder::new()
+            .cleared(Time(100), 3)
+            .full_clear(Time(200))
+            .berries(["a-01:12", "a-02:4"])
+            .checkpoints(["6", "9b"])
+            .build();
+        assert_eq!(stats.deaths, 3);
+        assert_eq!(stats.time_played, Time(200));
+        assert_eq!(stats.total_strawberries, 2);
+        assert_eq!(stats.checkpoints_reached(), 2);
+    }
+
+    #[test]
+    fn file_totals() {
+        let save_data = SaveDataBuilder::new()
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new()
+                .side(0, SideBuilder::new().deaths(5).time_played(Time(100)))
+                .side(1, SideBuilder::new().deaths(7).time_played(Time(200))))
+            .build();
+        assert_eq!(save_data.total_deaths, 12);
+        assert_eq!(save_data.time, Time(300));
+        // given totals are kept when they are possible
+        let save_data = SaveDataBuilder::new()
+            .total_deaths(100)
+            .time(Time(1000))
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new().side(0, SideBuilder::new().deaths(5)))
+            .build();
+        assert_eq!(save_data.total_deaths, 100);
+        assert_eq!(save_data.time, Time(1000));
+    }
+
+    #[test]
+    fn xml_round_trip() {
+        let xml = SaveDataBuilder::new()
+            .name("Theo")
+            .last_save("2023-05-01T19:00:00+09:00".parse().unwrap())
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new().side(0, SideBuilder::new().cleared(Time(100), 3).berries(["1:12"])))
+            .area("SJ", "SJ/1-Beginner/a", AreaBuilder::new().side(0, SideBuilder::new().full_clear(Time(300))))
+            .to_xml();
+        let save_data = SaveData::from_str(&xml).unwrap();
+        assert_eq!(save_data.name, "Theo");
+        assert_eq!(save_data.last_save, "2023-05-01T10:00:00Z".parse().unwrap());
+        let a_side = &save_data.map_stats[&code("Celeste/1-ForsakenCity", 0)];
+        assert_eq!(a_side.best_time, Time(100));
+        assert_eq!(a_side.collected_entities(), 1);
+        assert!(save_data.map_stats[&code("SJ/1-Beginner/a", 0)].full_clear);
+        assert!(save_data.levels["SJ"].contains(&code("SJ/1-Beginner/a", 0)));
+    }
+}
@@ -0,0 +1,460 @@
+use std::cell::Cell;
+use std::io::{ BufRead, Read };
+use serde::de::DeserializeOwned;
+use quick_xml::Reader;
+use quick_xml::de::Deserializer;
+use quick_xml::events::{ BytesStart, Event };
+use crate::error::{ Error, Position };
+
+// attributes the deserializer cannot do without
+const REQUIRED_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("LevelSetStats", &["Name"]),
+    ("AreaStats", &["SID", "Cassette"]),
+    ("AreaModeStats", &[
+        "Completed", "SingleRunCompleted", "FullClear", "Deaths", "TimePlayed",
+        "BestTime", "BestFullClearTime", "BestDashes", "BestDeaths", "HeartGem",
+    ]),
+];
+
+// attributes holding a Time as raw ticks
+const TIME_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("AreaModeStats", &["TimePlayed", "BestTime", "BestFullClearTime"]),
+];
+
+// elements directly under <SaveData> holding a Time as raw ticks
+const TIME_ELEMENTS: &[&str] = &["Time"];
+
+fn lookup<'a>(table: &[(&str, &'a [&'a str])], element: &str) -> &'a [&'a str] {
+    table.iter()
+        .find(|(e, _)| *e == element)
+        .map(|(_, attrs)| *attrs)
+        .unwrap_or(&[])
+}
+
+fn is_supported_version(version: &str) -> bool {
+    version.split('.').next().and_then(|major| major.trim().parse::<u64>().ok()) == Some(1)
+}
+
+struct Checker<'a> {
+    xml: &'a str,
+    // element names from the root, with the SID or Name attribute if any
+    path: Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    fn path(&self) -> String {
+        self.path.join("/")
+    }
+    fn position(&self, offset: usize) -> Option<Position> {
+        Some(Position::from_offset(self.xml, offset))
+    }
+    fn malformed(&self, message: String, offset: usize) -> Error {
+        Error::MalformedXml {
+            message,
+            position: self.position(offset),
+            path: self.path(),
+        }
+    }
+
+    // check an opening tag and push it to the path
+    fn start(&mut self, e: &BytesStart, offset: usize) -> Result<(), Error> {
+        let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+        if self.path.is_empty() && name != "SaveData" {
+            return Err(Error::NotSaveData { root: name });
+        }
+        let mut attributes = Vec::new();
+        for attr in e.attributes() {
+            let attr = attr.map_err(|err| self.malformed(err.to_string(), offset))?;
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+            let value = attr.unescape_value()
+                .map_err(|err| self.malformed(err.to_string(), offset))?
+                .into_owned();
+            attributes.push((key, value));
+        }
+        let id = attributes.iter()
+            .find(|(k, _)| k == "SID" || k == "Name")
+            .map(|(_, v)| v.clone());
+        self.path.push(match id {
+            Some(id) => format!("{}[{}]", name, id),
+            None => name.clone(),
+        });
+        for required in lookup(REQUIRED_ATTRIBUTES, &name) {
+            if !attributes.iter().any(|(k, _)| k == required) {
+                return Err(Error::MissingAttribute {
+                    name: required.to_string(),
+                    position: self.position(offset),
+                    path: self.path(),
+                });
+            }
+        }
+        for time in lookup(TIME_ATTRIBUTES, &name) {
+            if let Some((_, value)) = attributes.iter().find(|(k, _)| k == time) {
+                if value.trim().parse::<u64>().is_err() {
+                    return Err(Error::BadTime {
+                        value: value.clone(),
+                        position: self.position(offset),
+                        path: format!("{}/@{}", self.path(), time),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // check the text of an element directly under the root
+    fn text(&self, text: &str, offset: usize) -> Result<(), Error> {
+        if self.path.len() != 2 {
+            return Ok(());
+        }
+        let element = self.path[1].as_str();
+        if element == "Version" && !is_supported_version(text) {
+            return Err(Error::UnsupportedVersion { version: text.to_string() });
+        }
+        if TIME_ELEMENTS.contains(&element) && text.trim().parse::<u64>().is_err() {
+            return Err(Error::BadTime {
+                value: text.to_string(),
+                position: self.position(offset),
+                path: self.path(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// Walk the whole document once before deserializing it, so that errors carry
+// the line, column and element path that quick-xml's deserializer drops.
+pub(crate) fn check_save_xml(xml: &str) -> Result<(), Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut checker = Checker { xml, path: Vec::new() };
+    let mut has_root = false;
+    loop {
+        let offset = reader.buffer_position();
+        let event = reader.read_event()
+            .map_err(|e| checker.malformed(e.to_string(), reader.buffer_position()))?;
+        match event {
+            Event::Start(e) => {
+                checker.start(&e, offset)?;
+                has_root = true;
+            }
+            Event::Empty(e) => {
+                checker.start(&e, offset)?;
+                has_root = true;
+                checker.path.pop();
+            }
+            Event::End(_) => {
+                checker.path.pop();
+            }
+            Event::Text(e) => {
+                let text = e.unescape()
+                    .map_err(|err| checker.malformed(err.to_string(), offset))?;
+                checker.text(&text, offset)?;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !has_root {
+        return Err(checker.malformed("no root element".to_string(), xml.len()));
+    }
+    Ok(())
+}
+
+// A byte slice that counts how far the deserializer has read into it.
+struct Tracked<'a> {
+    rest: &'a [u8],
+    consumed: &'a Cell<usize>,
+}
+
+impl<'a> Read for Tracked<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.rest.read(buf)?;
+        self.consumed.set(self.consumed.get() + n);
+        Ok(n)
+    }
+}
+
+impl<'a> BufRead for Tracked<'a> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        Ok(self.rest)
+    }
+    fn consume(&mut self, amt: usize) {
+        self.rest = &self.rest[amt..];
+        self.consumed.set(self.consumed.get() + amt);
+    }
+}
+
+// an element of the document, as seen by locate
+struct Element {
+    name: String,
+    offset: usize,
+    path: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<String>,
+    text: String,
+    // the offset right after the element
+    end: usize,
+}
+
+// Walk the document and give the error the line, column and path of the
+// element it most likely comes from, given how far the deserializer had read.
+// The deserializer reads one event ahead, so offset is only a hint.
+fn locate(xml: &str, offset: usize, error: quick_xml::DeError) -> Error {
+    let mut reader = Reader::from_str(xml);
+    let mut elements: Vec<Element> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    // the end of every event but blank text, with the element it belongs to
+    let mut events: Vec<(usize, usize)> = Vec::new();
+    loop {
+        let start = reader.buffer_position();
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(_)) => {
+                path.pop();
+                if let Some(i) = open.pop() {
+                    elements[i].end = reader.buffer_position();
+                    events.push((reader.buffer_position(), i));
+                }
+                continue;
+            }
+            Ok(Event::Text(e)) => {
+                if let (Some(&i), Ok(text)) = (open.last(), e.unescape()) {
+                    if !text.trim().is_empty() {
+                        events.push((reader.buffer_position(), i));
+                    }
+                    elements[i].text.push_str(&text);
+                }
+                continue;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => continue,
+        };
+        let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+        let attributes: Vec<(String, String)> = e.attributes().flatten()
+            .map(|a| (
+                String::from_utf8_lossy(a.key.local_name().as_ref()).into_owned(),
+                a.unescape_value().map(|v| v.into_owned()).unwrap_or_default(),
+            ))
+            .collect();
+        if let Some(&parent) = open.last() {
+            elements[parent].children.push(name.clone());
+        }
+        let id = attributes.iter().find(|(k, _)| k == "SID" || k == "Name").map(|(_, v)| v.as_str());
+        path.push(match id {
+            Some(id) => format!("{}[{}]", name, id),
+            None => name.clone(),
+        });
+        let end = if empty { reader.buffer_position() } else { usize::MAX };
+        elements.push(Element {
+            name,
+            offset: start,
+            path: path.join("/"),
+            attributes,
+            children: Vec::new(),
+            text: String::new(),
+            end,
+        });
+        events.push((reader.buffer_position(), elements.len() - 1));
+        if empty {
+            path.pop();
+        }
+        else {
+            open.push(elements.len() - 1);
+        }
+    }
+    let position = |element: &Element| Some(Position::from_offset(xml, element.offset));
+    // serde reports missing fields as "missing field `@Name`" for attributes
+    // and "missing field `Name`" for child elements
+    let attribute = matches!(&error, quick_xml::DeError::Custom(message) if message.starts_with("missing field `@"));
+    let number = matches!(error, quick_xml::DeError::InvalidInt(_) | quick_xml::DeError::InvalidFloat(_));
+    // elements the deserializer was done with
+    let mut closed: Vec<&Element> = elements.iter().filter(|e| e.end <= offset).collect();
+    closed.sort_by_key(|e| e.end);
+    // the element of the last event read, and of the one before it
+    let mut last_read: Vec<usize> = Vec::new();
+    for &(_, i) in events.iter().rev().filter(|(end, _)| *end <= offset) {
+        if !last_read.contains(&i) {
+            last_read.push(i);
+        }
+        if last_read.len() == 2 {
+            break;
+        }
+    }
+    let last_read: Vec<&Element> = last_read.into_iter().map(|i| &elements[i]).collect();
+    match Error::from(error) {
+        Error::MissingAttribute { name, .. } => {
+            // the deserializer reports a missing field once the element is over
+            let element = if attribute {
+                closed.iter().rev().find(|e| !e.attributes.iter().any(|(k, _)| *k == name))
+            }
+            else {
+                // an element with other children, or one of a kind that has it elsewhere
+                let lacks = |e: &Element| !e.children.contains(&name)
+                    && (!e.children.is_empty() || elements.iter().any(|other| other.name == e.name && other.children.contains(&name)));
+                closed.iter().rev().find(|e| lacks(e))
+            };
+            match element {
+                Some(element) => Error::MissingAttribute { name, position: position(element), path: element.path.clone() },
+                None => Error::MissingAttribute { name, position: None, path: String::new() },
+            }
+        }
+        Error::MalformedXml { message, .. } => {
+            // the value that could not be read: quoted in the message, or for
+            // numbers, one that is neither a number nor a boolean
+            let quoted = |v: &str| message.contains(&format!("'{}'", v)) || message.contains(&format!("`{}`", v));
+            let bad_value = |v: &str| {
+                let v = v.trim();
+                if number {
+                    !v.is_empty() && v.parse::<f64>().is_err() && v.parse::<bool>().is_err()
+                }
+                else {
+                    quoted(v)
+                }
+            };
+            let element = last_read.iter()
+                .find(|e| e.attributes.iter().any(|(_, v)| bad_value(v)) || bad_value(&e.text))
+                .or(last_read.last());
+            match element {
+                Some(element) => {
+                    let path = match element.attributes.iter().find(|(_, v)| bad_value(v)) {
+                        Some((k, _)) => format!("{}/@{}", element.path, k),
+                        None => element.path.clone(),
+                    };
+                    Error::MalformedXml { message, position: position(element), path }
+                }
+                None => Error::MalformedXml { message, position: None, path: String::new() },
+            }
+        }
+        error => error,
+    }
+}
+
+// Deserialize a document that check_save_xml accepted. quick-xml's serde
+// errors have no location, so the input is re-scanned to find it.
+pub(crate) fn deserialize_save_xml<T: DeserializeOwned>(xml: &str) -> Result<T, Error> {
+    let consumed = Cell::new(0);
+    let mut deserializer = Deserializer::from_reader(Tracked { rest: xml.as_bytes(), consumed: &consumed });
+    T::deserialize(&mut deserializer).map_err(|e| locate(xml, consumed.get(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_data::SaveData;
+
+    const SAVE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<SaveData xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+  <Version>1.4.0.0</Version>
+  <Name>Madeline</Name>
+  <Time>100</Time>
+  <TotalDeaths>3</TotalDeaths>
+  <TotalStrawberries>0</TotalStrawberries>
+  <TotalJumps>0</TotalJumps>
+  <TotalWallJumps>0</TotalWallJumps>
+  <TotalDashes>0</TotalDashes>
+  <Areas>
+    <AreaStats ID="1" Cassette="false" SID="Celeste/1-ForsakenCity">
+      <Modes>
+        <AreaModeStats TotalStrawberries="0" Completed="false" SingleRunCompleted="false" FullClear="false" Deaths="3" TimePlayed="100" BestTime="0" BestFullClearTime="0" BestDashes="0" BestDeaths="0" HeartGem="false">
+          <Strawberries />
+          <Checkpoints />
+        </AreaModeStats>
+      </Modes>
+    </AreaStats>
+  </Areas>
+  <LevelSets />
+  <LevelSetRecycleBin />
+</SaveData>
+"#;
+
+    fn broken(from: &str, to: &str) -> Error {
+        assert_eq!(SAVE.matches(from).count(), 1);
+        SaveData::from_str(&SAVE.replace(from, to)).unwrap_err()
+    }
+
+    const AREA_MODE: &str = "SaveData/Areas/AreaStats[Celeste/1-ForsakenCity]/Modes/AreaModeStats";
+
+    #[test]
+    fn sample_is_valid() {
+        assert!(SaveData::from_str(SAVE).is_ok());
+    }
+
+    #[test]
+    fn settings_is_not_save_data() {
+        let settings = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<Settings>\n  <Fullscreen>false</Fullscreen>\n</Settings>\n";
+        assert_eq!(SaveData::from_str(settings).unwrap_err(), Error::NotSaveData { root: "Settings".to_string() });
+    }
+
+    #[test]
+    fn missing_attribute() {
+        assert_eq!(broken(" Deaths=\"3\"", ""), Error::MissingAttribute {
+            name: "Deaths".to_string(),
+            position: Some(Position { line: 14, column: 9 }),
+            path: AREA_MODE.to_string(),
+        });
+        assert_eq!(broken(" SID=\"Celeste/1-ForsakenCity\"", ""), Error::MissingAttribute {
+            name: "SID".to_string(),
+            position: Some(Position { line: 12, column: 5 }),
+            path: "SaveData/Areas/AreaStats".to_string(),
+        });
+    }
+
+    #[test]
+    fn bad_time() {
+        assert_eq!(broken("TimePlayed=\"100\"", "TimePlayed=\"1:00\""), Error::BadTime {
+            value: "1:00".to_string(),
+            position: Some(Position { line: 14, column: 9 }),
+            path: format!("{}/@TimePlayed", AREA_MODE),
+        });
+        assert_eq!(broken("<Time>100</Time>", "<Time>soon</Time>"), Error::BadTime {
+            value: "soon".to_string(),
+            position: Some(Position { line: 5, column: 9 }),
+            path: "SaveData/Time".to_string(),
+        });
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert_eq!(broken("1.4.0.0", "2.0.0.0"), Error::UnsupportedVersion { version: "2.0.0.0".to_string() });
+    }
+
+    #[test]
+    fn malformed_xml_has_a_position() {
+        match broken("</Modes>", "</Mode>") {
+            Error::MalformedXml { position, path, .. } => {
+                assert_eq!(position.map(|p| p.line), Some(18));
+                assert_eq!(path, "SaveData/Areas/AreaStats[Celeste/1-ForsakenCity]/Modes");
+            }
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn deserializer_errors_are_located() {
+        // values check_save_xml does not look at are reported by the deserializer
+        match broken("Deaths=\"3\"", "Deaths=\"three\"") {
+            Error::MalformedXml { position, path, .. } => {
+                assert_eq!(position, Some(Position { line: 14, column: 9 }));
+                assert_eq!(path, format!("{}/@Deaths", AREA_MODE));
+            }
+            e => panic!("unexpected {:?}", e),
+        }
+        match broken("HeartGem=\"false\"", "HeartGem=\"maybe\"") {
+            Error::MalformedXml { path, .. } => assert_eq!(path, format!("{}/@HeartGem", AREA_MODE)),
+            e => panic!("unexpected {:?}", e),
+        }
+        match broken("<TotalDeaths>3</TotalDeaths>", "<TotalDeaths>many</TotalDeaths>") {
+            Error::MalformedXml { position, path, .. } => {
+                assert_eq!(position, Some(Position { line: 6, column: 3 }));
+                assert_eq!(path, "SaveData/TotalDeaths");
+            }
+            e => panic!("unexpected {:?}", e),
+        }
+        assert_eq!(broken("  <TotalJumps>0</TotalJumps>\n", ""), Error::MissingAttribute {
+            name: "TotalJumps".to_string(),
+            position: Some(Position { line: 2, column: 1 }),
+            path: "SaveData".to_string(),
+        });
+    }
+}
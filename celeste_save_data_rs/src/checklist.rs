@@ -0,0 +1,106 @@
+use crate::save_data::{ SaveData, MapCode };
+use crate::map_data::GameData;
+
+// chapters with a cassette and B/C-sides: SID, name and the number of strawberries
+const VANILLA_CHAPTERS: &[(&str, &str, usize)] = &[
+    ("Celeste/1-ForsakenCity", "Forsaken City", 20),
+    ("Celeste/2-OldSite", "Old Site", 18),
+    ("Celeste/3-CelestialResort", "Celestial Resort", 25),
+    ("Celeste/4-GoldenRidge", "Golden Ridge", 29),
+    ("Celeste/5-MirrorTemple", "Mirror Temple", 31),
+    ("Celeste/6-Reflection", "Reflection", 0),
+    ("Celeste/7-Summit", "The Summit", 47),
+    ("Celeste/9-Core", "Core", 5),
+];
+
+const FAREWELL: &str = "Celeste/LostLevels";
+
+const SIDES: [&str; 3] = ["A-Side", "B-Side", "C-Side"];
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub item: String,
+    pub done: bool,
+    // (collected, needed) for items that are counted
+    pub progress: Option<(usize, usize)>,
+}
+
+// What the vanilla game needs for 100%
+#[derive(Debug, Clone)]
+pub struct Checklist {
+    pub checks: Vec<Check>,
+}
+
+impl Checklist {
+    // game_data tells the goldens and moon berries apart from the strawberries, see MapData::count_berries
+    pub fn vanilla(save_data: &SaveData, game_data: &GameData) -> Self {
+        let mut checks = Vec::new();
+        let stats = |sid: &str, side: usize| save_data.map_stats.get(&MapCode { sid: sid.to_string(), side });
+        let normal_berries = |sid: &str| {
+            let code = MapCode { sid: sid.to_string(), side: 0 };
+            let stats = save_data.map_stats.get(&code)?;
+            let map_data = game_data.levels().flat_map(|level| level.maps()).find(|map_data| map_data.code == code);
+            // without a maps.yaml entry every berry is normal
+            Some(match map_data {
+                Some(map_data) => map_data.count_berries(stats).normal,
+                None => stats.strawberries.entity_id.len(),
+            })
+        };
+        for (sid, name, berries) in VANILLA_CHAPTERS {
+            for (side, side_name) in SIDES.iter().enumerate() {
+                checks.push(Check {
+                    item: format!("{} {} crystal heart", name, side_name),
+                    done: stats(sid, side).map(|s| s.heart_gem).unwrap_or(false),
+                    progress: None,
+                });
+            }
+            checks.push(Check {
+                item: format!("{} cassette", name),
+                done: save_data.area_info.get(*sid).map(|info| info.cassette).unwrap_or(false),
+                progress: None,
+            });
+            if *berries > 0 {
+                // goldens maps.yaml does not register are still counted, so never count past the chapter total
+                let collected = std::cmp::min(normal_berries(sid).unwrap_or(0), *berries);
+                checks.push(Check {
+                    item: format!("{} strawberries", name),
+                    done: collected == *berries,
+                    progress: Some((collected, *berries)),
+                });
+            }
+        }
+        for (i, gem) in save_data.summit_gems.iter().enumerate() {
+            checks.push(Check {
+                item: format!("summit gem {}", i + 1),
+                done: *gem,
+                progress: None,
+            });
+        }
+        checks.push(Check {
+            item: "Farewell".to_string(),
+            done: stats(FAREWELL, 0).map(|s| s.completed).unwrap_or(false),
+            progress: None,
+        });
+        Self { checks }
+    }
+    pub fn missing(&self) -> impl Iterator<Item=&Check> {
+        self.checks.iter().filter(|c| !c.done)
+    }
+    pub fn is_complete(&self) -> bool {
+        self.checks.iter().all(|c| c.done)
+    }
+    // (done, total) over all checks
+    pub fn progress(&self) -> (usize, usize) {
+        (self.checks.iter().filter(|c| c.done).count(), self.checks.len())
+    }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{}] {}", if self.done { "x" } else { " " }, self.item)?;
+        if let Some((collected, needed)) = self.progress {
+            write!(f, " {}/{}", collected, needed)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+// The dialog key Everest looks up for a SID or level set, e.g. the name of a map:
+//   "StrawberryJam2021/1-Beginner/asteriskblue" -> "StrawberryJam2021_1_Beginner_asteriskblue"
+pub fn sid_to_key(sid: &str) -> String {
+    sid.replace(['/', '-', '+', ' '], "_")
+}
+
+// Drop the formatting of a dialog text: {n} becomes a space, other {...} commands
+// such as {# ff0000}, {~} or {big} are removed and whitespace is collapsed.
+pub fn strip_formatting(text: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        plain.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(end) => {
+                if rest[start + 1..start + end].trim() == "n" {
+                    plain.push(' ');
+                }
+                rest = &rest[start + end + 1..];
+            }
+            // an unclosed brace is just text
+            None => {
+                plain.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    plain.push_str(rest);
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// A Dialog/English.txt file of the game or a mod.
+//   # a comment
+//   KEY=a text
+//   that goes on{n}over lines
+// Keys are case-insensitive, as in the game.
+#[derive(Debug, Clone, Default)]
+pub struct Dialog {
+    entries: HashMap<String, String>,
+}
+
+impl Dialog {
+    pub fn parse(text: &str) -> Self {
+        let mut entries = HashMap::new();
+        let mut current: Option<(String, Vec<&str>)> = None;
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = Self::key_line(trimmed) {
+                if let Some((key, lines)) = current.take() {
+                    entries.insert(key, lines.join("\n"));
+                }
+                current = Some((key.to_uppercase(), if value.is_empty() { Vec::new() } else { vec![value] }));
+            }
+            else if let Some((_, lines)) = current.as_mut() {
+                if !trimmed.is_empty() {
+                    lines.push(trimmed);
+                }
+            }
+        }
+        if let Some((key, lines)) = current {
+            entries.insert(key, lines.join("\n"));
+        }
+        Self { entries }
+    }
+    // "KEY=value", where the key is a single word before the =, so "KEY =value" is a key and "a b = c" is text
+    fn key_line(line: &str) -> Option<(&str, &str)> {
+        let (key, value) = line.split_once('=')?;
+        let key = key.trim();
+        let is_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        is_key.then(|| (key, value.trim()))
+    }
+    // later files win, as when a mod overrides a vanilla key
+    pub fn merge(&mut self, other: Dialog) {
+        self.entries.extend(other.entries);
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    // the text as written, with formatting
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(&key.to_uppercase()).map(|text| text.as_str())
+    }
+    // the text without formatting, with {+KEY} replaced by the text of KEY
+    pub fn get_plain(&self, key: &str) -> Option<String> {
+        self.expand(key, 0).map(|text| strip_formatting(&text)).filter(|text| !text.is_empty())
+    }
+    fn expand(&self, key: &str, depth: usize) -> Option<String> {
+        let text = self.get(key)?;
+        // a reference loop in a broken file
+        if depth > 8 {
+            return Some(text.to_string());
+        }
+        let mut expanded = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("{+") {
+            let Some(end) = rest[start..].find('}') else { break };
+            expanded.push_str(&rest[..start]);
+            let reference = rest[start + 2..start + end].trim();
+            expanded.push_str(&self.expand(reference, depth + 1).unwrap_or_default());
+            rest = &rest[start + end + 1..];
+        }
+        expanded.push_str(rest);
+        Some(expanded)
+    }
+    // the name of a map or level set as the game shows it
+    pub fn map_name(&self, sid: &str) -> Option<String> {
+        self.get_plain(&sid_to_key(sid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "\
+# a comment
+StrawberryJam2021_1_Beginner_asteriskblue=
+  Forest Path
+SJ_Author =by {# 2a2a2a}Asterisk{#}
+sj_lines=first{n}second
+  third
+SJ_REF=Map: {+ StrawberryJam2021_1_Beginner_asteriskblue }!
+SJ_LOOP={+SJ_LOOP}x
+a b = not a key
+SJ_EMPTY=
+";
+
+    #[test]
+    fn parse() {
+        let dialog = Dialog::parse(TEXT);
+        assert_eq!(dialog.len(), 6);
+        assert_eq!(dialog.get("StrawberryJam2021_1_Beginner_asteriskblue"), Some("Forest Path"));
+        // keys are case-insensitive and may have spaces before the =
+        assert_eq!(dialog.get("sj_author"), Some("by {# 2a2a2a}Asterisk{#}"));
+        // a line that is not a key goes on the text before it
+        assert_eq!(dialog.get("SJ_LOOP"), Some("{+SJ_LOOP}x\na b = not a key"));
+        assert_eq!(dialog.get("SJ_LINES"), Some("first{n}second\nthird"));
+        assert_eq!(dialog.get("SJ_EMPTY"), Some(""));
+        assert_eq!(dialog.get("missing"), None);
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(strip_formatting("by {# 2a2a2a}Asterisk{#}"), "by Asterisk");
+        assert_eq!(strip_formatting("first{n}second\nthird"), "first second third");
+        assert_eq!(strip_formatting("{big}{~}wavy{/~}{/big}"), "wavy");
+        assert_eq!(strip_formatting("a { b"), "a { b");
+    }
+
+    #[test]
+    fn plain_text() {
+        let dialog = Dialog::parse(TEXT);
+        assert_eq!(dialog.get_plain("SJ_AUTHOR").as_deref(), Some("by Asterisk"));
+        assert_eq!(dialog.get_plain("SJ_REF").as_deref(), Some("Map: Forest Path!"));
+        // a loop stops instead of overflowing the stack
+        assert!(dialog.get_plain("SJ_LOOP").unwrap().starts_with("x"));
+        // an empty text is no text
+        assert_eq!(dialog.get_plain("SJ_EMPTY"), None);
+        assert_eq!(dialog.map_name("StrawberryJam2021/1-Beginner/asteriskblue").as_deref(), Some("Forest Path"));
+    }
+
+    #[test]
+    fn merge() {
+        let mut dialog = Dialog::parse("A=vanilla\nB=kept");
+        dialog.merge(Dialog::parse("a=mod"));
+        assert_eq!(dialog.get("A"), Some("mod"));
+        assert_eq!(dialog.get("B"), Some("kept"));
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(sid_to_key("StrawberryJam2021/1-Beginner/asteriskblue"), "StrawberryJam2021_1_Beginner_asteriskblue");
+        assert_eq!(sid_to_key("a+b c"), "a_b_c");
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+// 1-indexed position in the parsed text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    // compute the position of a byte offset in text
+    pub fn from_offset(text: &str, offset: usize) -> Self {
+        let mut offset = std::cmp::min(offset, text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Position {
+            line,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // the text is not well-formed xml, or does not match the shape of a save file
+    MalformedXml {
+        message: String,
+        position: Option<Position>,
+        path: String,
+    },
+    // the root element is not <SaveData>, e.g. settings.celeste was uploaded
+    NotSaveData {
+        root: String,
+    },
+    // a required attribute or element is missing
+    MissingAttribute {
+        name: String,
+        position: Option<Position>,
+        path: String,
+    },
+    // a time value is not a tick count
+    BadTime {
+        value: String,
+        position: Option<Position>,
+        path: String,
+    },
+    UnsupportedVersion {
+        version: String,
+    },
+    // maps.yaml does not match the expected schema
+    YamlSchema {
+        message: String,
+        position: Option<Position>,
+    },
+    // a level or SID registered in more than one file of a maps directory,
+    // or a level registered twice in one file (files has the file once)
+    Conflict {
+        key: String,
+        files: Vec<String>,
+    },
+    // a level or SID in a *.override.yaml that no file registers, e.g. a typo
+    UnknownOverride {
+        key: String,
+        file: String,
+    },
+    Io {
+        path: String,
+        message: String,
+    },
+    // a mod zip that cannot be read, or has no everest.yaml
+    BadArchive {
+        message: String,
+    },
+}
+
+fn write_location(f: &mut fmt::Formatter, position: &Option<Position>, path: &str) -> fmt::Result {
+    if !path.is_empty() {
+        write!(f, " at {}", path)?;
+    }
+    if let Some(position) = position {
+        write!(f, " ({})", position)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MalformedXml { message, position, path } => {
+                write!(f, "malformed save data: {}", message)?;
+                write_location(f, position, path)
+            }
+            Error::NotSaveData { root } => {
+                write!(f, "this isn't a Celeste save file (the root element is <{}>, maybe settings.celeste?)", root)
+            }
+            Error::MissingAttribute { name, position, path } => {
+                write!(f, "missing required \"{}\"", name)?;
+                write_location(f, position, path)
+            }
+            Error::BadTime { value, position, path } => {
+                write!(f, "bad time value \"{}\"", value)?;
+                write_location(f, position, path)
+            }
+            Error::UnsupportedVersion { version } => {
+                write!(f, "unsupported save data version \"{}\"", version)
+            }
+            Error::YamlSchema { message, position } => {
+                write!(f, "cannot parse yaml: {}", message)?;
+                write_location(f, position, "")
+            }
+            Error::Conflict { key, files } if files.len() == 1 => {
+                write!(f, "{} is registered twice in {}", key, files[0])
+            }
+            Error::Conflict { key, files } => {
+                write!(f, "{} is registered in more than one file ({})", key, files.join(", "))
+            }
+            Error::UnknownOverride { key, file } => {
+                write!(f, "{}: {} is not registered in any file", file, key)
+            }
+            Error::Io { path, message } => {
+                write!(f, "cannot read {}: {}", path, message)
+            }
+            Error::BadArchive { message } => {
+                write!(f, "bad mod archive: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        let position = e.location().map(|l| Position { line: l.line(), column: l.column() });
+        let mut message = e.to_string();
+        // serde_yaml appends the location to its message
+        if let Some(p) = position {
+            let suffix = format!(" at line {} column {}", p.line, p.column);
+            if let Some(stripped) = message.strip_suffix(&suffix) {
+                message = stripped.to_string();
+            }
+        }
+        Error::YamlSchema { message, position }
+    }
+}
+
+impl From<quick_xml::DeError> for Error {
+    fn from(e: quick_xml::DeError) -> Self {
+        match e {
+            quick_xml::DeError::Custom(message) => {
+                // serde reports missing fields as "missing field `@Name`"
+                match message.strip_prefix("missing field `").and_then(|s| s.strip_suffix('`')) {
+                    Some(name) => Error::MissingAttribute {
+                        name: name.trim_start_matches('@').to_string(),
+                        position: None,
+                        path: String::new(),
+                    },
+                    None => Error::MalformedXml { message, position: None, path: String::new() },
+                }
+            }
+            e => Error::MalformedXml { message: e.to_string(), position: None, path: String::new() },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_from_offset() {
+        let text = "<a>\n  <b/>\n  <ç/>\n</a>";
+        assert_eq!(Position::from_offset(text, 0), Position { line: 1, column: 1 });
+        assert_eq!(Position::from_offset(text, text.find("<b").unwrap()), Position { line: 2, column: 3 });
+        // columns count characters, not bytes
+        assert_eq!(Position::from_offset(text, text.find("/>\n</a>").unwrap()), Position { line: 3, column: 5 });
+        assert_eq!(Position::from_offset(text, text.len() + 10), Position { line: 4, column: 5 });
+    }
+
+    #[test]
+    fn display_has_the_path_and_position() {
+        let error = Error::MissingAttribute {
+            name: "Deaths".to_string(),
+            position: Some(Position { line: 14, column: 9 }),
+            path: "SaveData/Areas/AreaStats[Celeste/1-ForsakenCity]".to_string(),
+        };
+        assert_eq!(error.to_string(), "missing required \"Deaths\" at SaveData/Areas/AreaStats[Celeste/1-ForsakenCity] (line 14, column 9)");
+        let error = Error::BadTime { value: "1:00".to_string(), position: None, path: String::new() };
+        assert_eq!(error.to_string(), "bad time value \"1:00\"");
+    }
+
+    #[test]
+    fn yaml_errors_keep_their_position() {
+        let error: Error = serde_yaml::from_str::<Vec<u64>>("- 1\n- two\n").unwrap_err().into();
+        match error {
+            Error::YamlSchema { message, position } => {
+                assert_eq!(position.map(|p| p.line), Some(2));
+                assert!(!message.contains(" at line "), "{}", message);
+            }
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+}
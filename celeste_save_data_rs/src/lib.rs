@@ -1,3 +1,18 @@
 pub mod save_data;
 pub mod map_data;
 pub mod time;
+pub mod error;
+pub mod merge;
+pub mod modsave;
+pub mod checklist;
+pub mod validate;
+pub mod builder;
+pub mod progress;
+pub mod lint;
+pub mod dialog;
+#[cfg(feature="zip")]
+pub mod mod_zip;
+mod check;
+mod writer;
+
+pub use error::Error;
@@ -0,0 +1,216 @@
+use std::collections::{ HashMap, HashSet };
+use std::fmt;
+use crate::map_data::{ GameData, LIST_TAG };
+use crate::save_data::SaveData;
+
+// Something in maps.yaml that is likely a mistake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    // the same SID in more than one level group, levels tagged list are not counted
+    DuplicateSid {
+        sid: String,
+        levels: Vec<String>,
+    },
+    // get_level_data only ever finds the first of these
+    DuplicateLevel {
+        level: String,
+    },
+    // the same side listed twice for a map
+    DuplicateSide {
+        sid: String,
+        side: usize,
+    },
+    MissingEnglishName {
+        sid: String,
+    },
+    // empty in every language, shown by its SID
+    MissingName {
+        sid: String,
+    },
+    // a side past C-side without a label, shown with the label MapData::side_label falls back to
+    UnlabeledSide {
+        sid: String,
+        side: usize,
+        shown_as: String,
+    },
+    EmptyLevel {
+        level: String,
+    },
+    // no save of the corpus has ever played the map, maybe a typo in the SID
+    UnseenSid {
+        sid: String,
+    },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lint::DuplicateSid { sid, levels } => {
+                write!(f, "{} is registered in more than one level ({})", sid, levels.join(", "))
+            }
+            Lint::DuplicateLevel { level } => {
+                write!(f, "level {} is registered more than once, only the first is used", level)
+            }
+            Lint::DuplicateSide { sid, side } => {
+                write!(f, "{}: side {} is listed more than once", sid, side)
+            }
+            Lint::MissingEnglishName { sid } => {
+                write!(f, "{}: no en name", sid)
+            }
+            Lint::MissingName { sid } => {
+                write!(f, "{}: no name in any language, shown as its SID", sid)
+            }
+            Lint::UnlabeledSide { sid, side, shown_as } => {
+                write!(f, "{}: side {} is not 0-2 and has no label, shown as -{}", sid, side, shown_as)
+            }
+            Lint::EmptyLevel { level } => {
+                write!(f, "level {} has no maps", level)
+            }
+            Lint::UnseenSid { sid } => {
+                write!(f, "{} is not in any of the save files", sid)
+            }
+        }
+    }
+}
+
+impl GameData {
+    // Check maps.yaml on its own, in the order of the file
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut lints = Vec::new();
+
+        let mut seen_levels = HashSet::new();
+        let mut sid_levels: HashMap<String, Vec<String>> = HashMap::new();
+        let mut sid_order = Vec::new();
+        for level in self.levels() {
+            if !seen_levels.insert(level.level.as_str()) {
+                lints.push(Lint::DuplicateLevel { level: level.level.clone() });
+            }
+            if level.maps().len() == 0 {
+                lints.push(Lint::EmptyLevel { level: level.level.clone() });
+            }
+            let mut seen_codes = HashSet::new();
+            for map_data in level.maps() {
+                let sid = map_data.code.sid.clone();
+                let side = map_data.code.side;
+                if !seen_codes.insert(map_data.code.clone()) {
+                    lints.push(Lint::DuplicateSide { sid: sid.clone(), side });
+                }
+                if side == 0 && map_data.name.any_name().is_none() {
+                    lints.push(Lint::MissingName { sid: sid.clone() });
+                }
+                else if side == 0 && map_data.name.get("en").is_none() {
+                    lints.push(Lint::MissingEnglishName { sid: sid.clone() });
+                }
+                if side > 2 && map_data.side_label.is_none() && map_data.side_title.is_none() {
+                    lints.push(Lint::UnlabeledSide { sid: sid.clone(), side, shown_as: map_data.side_label() });
+                }
+                // lists such as list_love pick maps of other levels on purpose
+                if level.has_tag(LIST_TAG) {
+                    continue;
+                }
+                let levels = sid_levels.entry(sid.clone()).or_insert_with(|| {
+                    sid_order.push(sid);
+                    Vec::new()
+                });
+                if !levels.contains(&level.level) {
+                    levels.push(level.level.clone());
+                }
+            }
+        }
+        for sid in sid_order {
+            let levels = sid_levels.remove(&sid).unwrap_or_default();
+            if levels.len() > 1 {
+                lints.push(Lint::DuplicateSid { sid, levels });
+            }
+        }
+        lints
+    }
+    // Also flag the maps none of saves has played. Only useful with many saves, e.g. all in the db.
+    pub fn lint_with_saves<'a, I>(&self, saves: I) -> Vec<Lint>
+        where I: IntoIterator<Item=&'a SaveData>,
+    {
+        let mut lints = self.lint();
+        let played = saves.into_iter()
+            .flat_map(|save_data| save_data.area_info.keys().cloned())
+            .collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        for map_data in self.levels().flat_map(|level| level.maps()) {
+            let sid = map_data.code.sid;
+            if !played.contains(&sid) && seen.insert(sid.clone()) {
+                lints.push(Lint::UnseenSid { sid });
+            }
+        }
+        lints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{ SaveDataBuilder, AreaBuilder };
+
+    const MAPS: &str = "
+- level: 'A'
+  name: 'A'
+  maps:
+    - sid: 'A/a'
+      name: { en: 'a' }
+      sides: [0, 0, 3]
+    - sid: 'A/b'
+      name: { ja: 'b' }
+      sides: [0, { side: 4, label: 'Remix' }]
+    - sid: 'A/c'
+      name: { en: '' }
+      sides: [0]
+- level: 'B'
+  name: 'B'
+  maps:
+    - sid: 'A/a'
+      name: { en: 'a' }
+      sides: [0]
+- level: 'list_a'
+  name: 'list'
+  tags: [list]
+  maps:
+    - sid: 'A/b'
+      name: { en: 'b' }
+      sides: [0]
+- level: 'B'
+  name: 'B again'
+  maps: []
+";
+
+    #[test]
+    fn lint() {
+        let lints = GameData::from_str(MAPS).unwrap().lint();
+        assert_eq!(lints, vec![
+            Lint::DuplicateSide { sid: "A/a".to_string(), side: 0 },
+            Lint::UnlabeledSide { sid: "A/a".to_string(), side: 3, shown_as: "D".to_string() },
+            Lint::MissingEnglishName { sid: "A/b".to_string() },
+            Lint::MissingName { sid: "A/c".to_string() },
+            Lint::DuplicateLevel { level: "B".to_string() },
+            Lint::EmptyLevel { level: "B".to_string() },
+            // A/b is also in list_a, which does not count
+            Lint::DuplicateSid { sid: "A/a".to_string(), levels: vec!["A".to_string(), "B".to_string()] },
+        ]);
+    }
+
+    #[test]
+    fn clean() {
+        let maps = "- { level: 'A', name: 'A', maps: [{ sid: 'A/a', name: { en: 'a' }, sides: [0, 1, 2] }] }";
+        assert!(GameData::from_str(maps).unwrap().lint().is_empty());
+    }
+
+    #[test]
+    fn unseen() {
+        let save_data = SaveDataBuilder::new()
+            .area("A", "A/a", AreaBuilder::new())
+            .area("A", "A/b", AreaBuilder::new())
+            .build();
+        let game_data = GameData::from_str(MAPS).unwrap();
+        let unseen = game_data.lint_with_saves([&save_data]).into_iter()
+            .filter(|lint| matches!(lint, Lint::UnseenSid { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(unseen, vec![Lint::UnseenSid { sid: "A/c".to_string() }]);
+    }
+}
@@ -1,41 +1,359 @@
-use serde::Deserialize;
-use std::collections::HashMap;
-use crate::save_data::MapCode;
+use serde::{ Deserialize, Serialize };
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::path::Path;
+use std::cmp::Ordering;
+use crate::save_data::{ SaveData, MapCode, AreaModeStats };
+use crate::error::Error;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(from = "Vec<LevelData>")]
+pub struct GameData {
+    levels: Vec<LevelData>,
+    // SIDs of the entries with no name in any language
+    unnamed: Vec<String>,
+}
+
+impl From<Vec<LevelData>> for GameData {
+    fn from(levels: Vec<LevelData>) -> Self {
+        Self::from_levels(levels)
+    }
+}
+
+// names and orders to patch, from a *.override.yaml file
+//   levels:
+//     - { level: 'StrawberryJam2021/1-Beginner', name: 'SJ Beginner', order: 10 }
+//   maps:
+//     - { sid: 'StrawberryJam2021/1-Beginner/asteriskblue', name: { ja: '森の小道' } }
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct OverrideRaw {
+    levels: Vec<LevelOverrideRaw>,
+    maps: Vec<MapOverrideRaw>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LevelOverrideRaw {
+    level: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    order: Option<i64>,
+}
 
 #[derive(Deserialize, Debug)]
-pub struct GameData(Vec<LevelData>);
+struct MapOverrideRaw {
+    sid: String,
+    name: Name,
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path).map_err(|e| Error::Io { path: path.display().to_string(), message: e.to_string() })
+}
+
+// a yaml error of one of the files in a directory
+fn in_file(path: &Path, e: serde_yaml::Error) -> Error {
+    match Error::from(e) {
+        Error::YamlSchema { message, position } => Error::YamlSchema { message: format!("{}: {}", path.display(), message), position },
+        e => e,
+    }
+}
+
+impl GameData {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(yml_str: &str) -> Result<Self, Error> {
+        Ok(Self::from_levels(serde_yaml::from_str(yml_str)?))
+    }
+    // a maps.yaml file, or a directory of them
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            Self::from_dir(path)
+        }
+        else {
+            Self::from_str(&read_file(path)?)
+        }
+    }
+    // Every *.yaml in dir in the order of the file names, e.g. one file per collab.
+    // A level registered twice, or a SID registered in two files, is an error.
+    // Levels tagged LIST_TAG reuse the SIDs of other levels, so they are left out of the SID check.
+    // *.override.yaml files are read last and only patch names and orders of levels and SIDs that exist.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|e| Error::Io { path: dir.display().to_string(), message: e.to_string() })?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| Error::Io { path: dir.display().to_string(), message: e.to_string() })?.path();
+            if matches!(path.extension().and_then(|e| e.to_str()), Some("yaml") | Some("yml")) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        let (overrides, paths): (Vec<_>, Vec<_>) = paths.into_iter()
+            .partition(|path| path.file_stem().and_then(|s| s.to_str()).map(|s| s.ends_with(".override")).unwrap_or(false));
+
+        let mut levels: Vec<LevelData> = Vec::new();
+        let mut level_files: HashMap<String, String> = HashMap::new();
+        let mut sid_files: HashMap<String, String> = HashMap::new();
+        for path in paths {
+            let file = path.display().to_string();
+            let file_levels: Vec<LevelData> = serde_yaml::from_str(&read_file(&path)?).map_err(|e| in_file(&path, e))?;
+            for level in file_levels.iter() {
+                if let Some(other) = level_files.insert(level.level.clone(), file.clone()) {
+                    let files = if other == file { vec![file] } else { vec![other, file] };
+                    return Err(Error::Conflict { key: level.level.clone(), files });
+                }
+            }
+            // the same SID in one file is left to GameData::lint
+            let sids = file_levels.iter()
+                .filter(|level| !level.has_tag(LIST_TAG))
+                .flat_map(|level| level.maps.iter())
+                .map(|map| map.sid.clone())
+                .collect::<HashSet<_>>();
+            for sid in sids {
+                if let Some(other) = sid_files.insert(sid.clone(), file.clone()) {
+                    return Err(Error::Conflict { key: sid, files: vec![other, file] });
+                }
+            }
+            levels.extend(file_levels);
+        }
+        for path in overrides {
+            let file = path.display().to_string();
+            let patch: OverrideRaw = serde_yaml::from_str(&read_file(&path)?).map_err(|e| in_file(&path, e))?;
+            for level_patch in patch.levels {
+                let level = levels.iter_mut().find(|level| level.level == level_patch.level)
+                    .ok_or_else(|| Error::UnknownOverride { key: level_patch.level.clone(), file: file.clone() })?;
+                if let Some(name) = &level_patch.name {
+                    level.name = name.clone();
+                }
+                if level_patch.order.is_some() {
+                    level.order = level_patch.order;
+                }
+            }
+            for map_patch in patch.maps {
+                let mut patched = false;
+                // a SID in a list level is the same map, so all of them are renamed
+                for map in levels.iter_mut().flat_map(|level| level.maps.iter_mut()).filter(|map| map.sid == map_patch.sid) {
+                    map.name.patch(&map_patch.name);
+                    patched = true;
+                }
+                if !patched {
+                    return Err(Error::UnknownOverride { key: map_patch.sid, file });
+                }
+            }
+        }
+        Ok(Self::from_levels(levels))
+    }
+    // entries with no name are loaded as is and shown by their SID, GameData::unnamed lists them
+    fn from_levels(mut levels: Vec<LevelData>) -> Self {
+        // levels without an order keep their place after the ordered ones
+        levels.sort_by_key(|level| level.order.unwrap_or(i64::MAX));
+        let mut unnamed: Vec<String> = Vec::new();
+        for map in levels.iter().flat_map(|level| level.maps.iter()) {
+            if map.name.any_name().is_none() && !unnamed.contains(&map.sid) {
+                unnamed.push(map.sid.clone());
+            }
+        }
+        GameData { levels, unnamed }
+    }
+    // the SIDs loaded without a name, e.g. a template left at en: ''
+    pub fn unnamed(&self) -> &[String] {
+        &self.unnamed
+    }
+    pub fn levels(&self) -> impl Iterator<Item=&LevelData> {
+        self.levels.iter()
+    }
+    pub fn get_level_data<'a>(&'a self, level: &str) -> Option<&'a LevelData> {
+        self.levels.iter().find(|d| d.level == level)
+    }
+    // MapData::count_berries of the map, every berry is normal for maps not in maps.yaml
+    pub fn count_berries(&self, code: &MapCode, stats: &AreaModeStats) -> BerryCounts {
+        match self.levels().flat_map(|level| level.maps()).find(|map_data| map_data.code == *code) {
+            Some(map_data) => map_data.count_berries(stats),
+            None => BerryCounts { normal: stats.collected_entities(), ..Default::default() },
+        }
+    }
+    // levels by LevelData::group, groups in the order their first level appears
+    pub fn groups(&self) -> Vec<(&str, Vec<&LevelData>)> {
+        let mut groups: Vec<(&str, Vec<&LevelData>)> = Vec::new();
+        for level in self.levels.iter() {
+            match groups.iter_mut().find(|(group, _)| *group == level.group()) {
+                Some((_, levels)) => levels.push(level),
+                None => groups.push((level.group(), vec![level])),
+            }
+        }
+        groups
+    }
+    pub fn levels_tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item=&'a LevelData> {
+        self.levels.iter().filter(move |level| level.has_tag(tag))
+    }
+}
+
+// the tag of levels that collect maps of other levels, e.g. list_love, so their SIDs are not duplicates
+pub const LIST_TAG: &str = "list";
+
+// compare with the numbers in the strings as numbers, "2-Intermediate" before "10-Bonus"
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    fn chunks(s: &str) -> Vec<(bool, String)> {
+        let mut chunks: Vec<(bool, String)> = Vec::new();
+        for c in s.chars() {
+            let digit = c.is_ascii_digit();
+            match chunks.last_mut() {
+                Some((d, chunk)) if *d == digit => chunk.push(c),
+                _ => chunks.push((digit, c.to_string())),
+            }
+        }
+        chunks
+    }
+    for (x, y) in chunks(a).iter().zip(chunks(b).iter()) {
+        let ord = match (x, y) {
+            ((true, x), (true, y)) => {
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            ((_, x), (_, y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
 
+// The level sets of save_data that are not in maps.yaml, as maps.yaml entries to fill in.
 impl GameData {
-    pub fn from_str(yml_str: &str) -> Result<Self, String> {
-        serde_yaml::from_str(yml_str).map_err(|e| format!("cannot parse yaml: {:?}", e))
+    pub fn unknown_levels(&self, save_data: &SaveData) -> Vec<String> {
+        let mut levels = save_data.levels.keys()
+            .filter(|level| self.get_level_data(level).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        levels.sort_by(|a, b| natural_cmp(a, b));
+        levels
+    }
+    // names gives the English name of a level or SID when it knows one, e.g. from Dialog/English.txt
+    pub fn templates<F>(&self, save_data: &SaveData, names: F) -> Vec<LevelData>
+        where F: Fn(&str) -> Option<String>,
+    {
+        self.unknown_levels(save_data).iter()
+            .map(|level| LevelData::template(level, save_data, &names))
+            .collect()
+    }
+}
+
+impl LevelData {
+    // side 0 of every area, and the other sides that have been played.
+    // Maps names does not know get a blank en: '', which loads as is and is reported by GameData::lint.
+    pub fn template<F>(level: &str, save_data: &SaveData, names: F) -> Self
+        where F: Fn(&str) -> Option<String>,
+    {
+        let mut sides: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for code in save_data.levels.get(level).into_iter().flatten() {
+            let played = save_data.map_stats.get(code).map(|stats| stats.completed || stats.time_played.0 > 0).unwrap_or(false);
+            if code.side == 0 || played {
+                sides.entry(code.sid.as_str()).or_default().push(code.side);
+            }
+        }
+        let mut sids = sides.into_iter().collect::<Vec<_>>();
+        sids.sort_by(|a, b| natural_cmp(a.0, b.0));
+        let mut level_data = LevelData::new(level, &names(level).unwrap_or_else(|| level.to_string()));
+        for (sid, sides) in sids {
+            let name = Name::new([("en".to_string(), names(sid).unwrap_or_default())]);
+            level_data.push_map(sid, name, sides, None);
+        }
+        level_data
     }
-    pub fn levels<'a>(&'a self) -> impl Iterator<Item=&'a LevelData> {
-        self.0.iter().map(|d| d)
+    // an empty level set, to be filled with push_map
+    pub fn new(level: &str, name: &str) -> Self {
+        LevelData {
+            level: level.to_string(),
+            name: name.to_string(),
+            order: None,
+            group: None,
+            tier: None,
+            tags: Vec::new(),
+            difficulty: None,
+            maps: Vec::new(),
+        }
+    }
+    pub fn push_map(&mut self, sid: &str, name: Name, mut sides: Vec<usize>, mod_name: Option<String>) {
+        sides.sort();
+        sides.dedup();
+        self.maps.push(MapDataRaw {
+            sid: sid.to_string(),
+            name,
+            sides: sides.into_iter().map(SideRaw::Index).collect(),
+            authors: Vec::new(),
+            mod_name,
+            gamebanana: None,
+            difficulty: None,
+            chapter: None,
+        });
     }
-    pub fn get_level_data<'a, 'b>(&'a self, level: &'b str) -> Option<&'a LevelData> {
-        self.0.iter().find(|d| d.level == level)
+    // every map has a name, so the entry can go into maps.yaml as is
+    pub fn is_named(&self) -> bool {
+        self.maps.iter().all(|map| map.name.any_name().is_some())
     }
 }
 
-#[derive(Deserialize, Debug)]
+// entries in the format of maps.yaml
+pub fn to_yaml(levels: &[LevelData]) -> Result<String, Error> {
+    Ok(serde_yaml::to_string(levels)?)
+}
+
+#[derive(Deserialize, Serialize, Debug)]
 pub struct LevelData {
     pub level: String,
     pub name: String,
+    // display order, smaller first
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub order: Option<i64>,
+    // the mod or collection, e.g. "Strawberry Jam", see LevelData::group
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub group: Option<String>,
+    // e.g. "Beginner"
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub tier: Option<String>,
+    // e.g. "collab", "contest", LIST_TAG
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    pub difficulty: Option<String>,
     maps: Vec<MapDataRaw>,
 }
 
 impl LevelData {
-    pub fn maps(&self) -> impl ExactSizeIterator + Iterator<Item=MapData> {
+    // the group in maps.yaml, or the first part of the level, "StrawberryJam2021" for "StrawberryJam2021/1-Beginner"
+    pub fn group(&self) -> &str {
+        match &self.group {
+            Some(group) => group.as_str(),
+            None => self.level.split('/').next().unwrap_or(&self.level),
+        }
+    }
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+    pub fn maps(&self) -> impl ExactSizeIterator<Item=MapData> {
         let mut codes = Vec::new();
         for map in self.maps.iter() {
             for side in map.sides.iter() {
                 codes.push( MapData {
                     code: MapCode {
                         sid: map.sid.clone(),
-                        side: *side,
+                        side: side.side(),
                     },
                     name: map.name.clone(),
                     multi_side: map.sides.len() > 1,
+                    berry_kinds: side.berry_kinds(),
+                    berries: side.berries(),
+                    berry_keys: side.berry_keys(),
+                    checkpoint_rooms: side.checkpoint_rooms(),
+                    side_label: side.label(),
+                    side_title: side.name(),
+                    // a side can have its own mapper and difficulty, e.g. a B-side remixed by someone else
+                    authors: side.detail().map(|d| d.authors.clone()).filter(|a| !a.is_empty()).unwrap_or_else(|| map.authors.clone()),
+                    mod_name: map.mod_name.clone(),
+                    gamebanana: map.gamebanana,
+                    difficulty: side.detail().and_then(|d| d.difficulty.clone()).or_else(|| map.difficulty.clone()),
+                    chapter: map.chapter,
                 })
             }
         }
@@ -48,43 +366,437 @@ pub struct MapData {
     pub code: MapCode,
     pub name: Name,
     pub multi_side: bool,
+    // berries registered in maps.yaml as something other than a normal berry
+    pub berry_kinds: HashMap<String, BerryKind>,
+    // strawberries the side has, when registered in maps.yaml
+    pub berries: Option<usize>,
+    // EntityID keys of those strawberries, when registered
+    pub berry_keys: Vec<String>,
+    // rooms of the checkpoints in the order of the map, when registered
+    pub checkpoint_rooms: Vec<String>,
+    // "D", "Remix", ... instead of A/B/C
+    pub side_label: Option<String>,
+    // a name of its own, used as is without the side suffix
+    pub side_title: Option<Name>,
+    pub authors: Vec<String>,
+    // the mod the map comes from, e.g. "StrawberryJam2021"
+    pub mod_name: Option<String>,
+    // GameBanana item id of the mod
+    pub gamebanana: Option<u64>,
+    pub difficulty: Option<String>,
+    // chapter number in its level set
+    pub chapter: Option<u32>,
 }
 
 impl MapData {
+    // the label of the side, A/B/C unless maps.yaml gives one
+    pub fn side_label(&self) -> String {
+        match &self.side_label {
+            Some(label) => label.clone(),
+            None => side_label(self.code.side),
+        }
+    }
     fn side_name(&self) -> String {
-        if self.multi_side {
-            ["-A", "-B", "-C"][self.code.side].to_owned()
+        if self.multi_side || self.side_label.is_some() {
+            format!("-{}", self.side_label())
         }
         else {
             "".to_owned()
         }
     }
     pub fn get_name(&self) -> String {
-        format!("{}{}", self.name.get_name(), self.side_name())
+        self.local_name_with(&["en"])
     }
-    pub fn try_local_name<'a, 'b>(&'a self, lang: &'b str) -> String {
-        format!("{}{}", self.name.try_local_name(lang), self.side_name())
+    pub fn try_local_name(&self, lang: &str) -> String {
+        self.local_name_with(&fallback_chain(lang))
+    }
+    // the name in the first language of chain that has one, then any name, then the SID
+    pub fn local_name_with<S: AsRef<str>>(&self, chain: &[S]) -> String {
+        let resolve = |name: &Name| name.resolve(chain).or_else(|| name.any_name()).map(|n| n.to_string());
+        if let Some(title) = self.side_title.as_ref().and_then(resolve) {
+            return title;
+        }
+        let name = resolve(&self.name).unwrap_or_else(|| self.code.sid.clone());
+        format!("{}{}", name, self.side_name())
+    }
+    // EntityID keys are just "room:id", so the kind comes from the registry
+    pub fn berry_kind(&self, key: &str) -> BerryKind {
+        self.berry_kinds.get(key).copied().unwrap_or(BerryKind::Normal)
+    }
+    // the total given in maps.yaml, or the number of registered keys
+    pub fn berry_total(&self) -> Option<usize> {
+        match self.berries {
+            Some(total) => Some(total),
+            None if !self.berry_keys.is_empty() => Some(self.berry_keys.len()),
+            None => None,
+        }
+    }
+    // registered strawberries not in the save file, empty when no key is registered
+    pub fn missing_berries<'a>(&'a self, stats: Option<&AreaModeStats>) -> Vec<&'a str> {
+        let collected = stats.map(|stats| stats.strawberries.entity_id.iter().map(|e| e.key.as_str()).collect::<HashSet<_>>()).unwrap_or_default();
+        self.berry_keys.iter().map(|key| key.as_str()).filter(|key| !collected.contains(key)).collect()
+    }
+    pub fn has_author(&self, author: &str) -> bool {
+        self.authors.iter().any(|a| a.eq_ignore_ascii_case(author))
+    }
+    // "by A & B", None when no author is registered
+    pub fn credits(&self) -> Option<String> {
+        (!self.authors.is_empty()).then(|| format!("by {}", self.authors.join(" & ")))
+    }
+    // the furthest checkpoint reached, 1 for the first, None when none is.
+    // Without checkpoint_rooms the count is taken, as checkpoints are reached one after another.
+    pub fn furthest_checkpoint(&self, stats: &AreaModeStats) -> Option<usize> {
+        if self.checkpoint_rooms.is_empty() {
+            let reached = stats.checkpoints_reached();
+            return (reached > 0).then_some(reached);
+        }
+        self.checkpoint_rooms.iter().rposition(|room| stats.checkpoints.rooms.contains(room)).map(|i| i + 1)
+    }
+    pub fn count_berries(&self, stats: &AreaModeStats) -> BerryCounts {
+        let mut counts = BerryCounts::default();
+        for entity in stats.strawberries.entity_id.iter() {
+            match self.berry_kind(&entity.key) {
+                BerryKind::Normal => counts.normal += 1,
+                BerryKind::Golden => counts.golden += 1,
+                BerryKind::WingedGolden => counts.winged_golden += 1,
+                BerryKind::Moon => counts.moon += 1,
+            }
+        }
+        counts
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BerryKind {
+    Normal,
+    Golden,
+    WingedGolden,
+    Moon,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BerryCounts {
+    pub normal: usize,
+    pub golden: usize,
+    pub winged_golden: usize,
+    pub moon: usize,
+}
+
+impl BerryCounts {
+    // everything the SB column counts, i.e. all but goldens
+    pub fn strawberries(&self) -> usize {
+        self.normal + self.moon
+    }
+    pub fn goldens(&self) -> usize {
+        self.golden + self.winged_golden
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
 struct MapDataRaw {
     sid: String,
     name: Name,
-    sides: Vec<usize>,
+    sides: Vec<SideRaw>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    authors: Vec<String>,
+    #[serde(default, skip_serializing_if="Option::is_none", rename="mod")]
+    mod_name: Option<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    gamebanana: Option<u64>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    difficulty: Option<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    chapter: Option<u32>,
+}
+
+// a side is either just its index or an object with the berry registry
+//   sides: [{ side: 0, berries: 20, strawberries: ['a-01:12', ...] }, { side: 1, goldens: ['b-00:12'] }]
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(untagged)]
+enum SideRaw {
+    Index(usize),
+    Detail(Box<SideDetailRaw>),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct SideDetailRaw {
+    side: usize,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    berries: Option<usize>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    strawberries: Vec<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    label: Option<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    name: Option<Name>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    authors: Vec<String>,
+    #[serde(default, skip_serializing_if="Option::is_none")]
+    difficulty: Option<String>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    checkpoints: Vec<String>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    goldens: Vec<String>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    winged_goldens: Vec<String>,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    moons: Vec<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-pub struct Name(HashMap<String, String>);
+impl SideRaw {
+    fn side(&self) -> usize {
+        match self {
+            SideRaw::Index(side) => *side,
+            SideRaw::Detail(detail) => detail.side,
+        }
+    }
+    fn berries(&self) -> Option<usize> {
+        match self {
+            SideRaw::Index(_) => None,
+            SideRaw::Detail(detail) => detail.berries,
+        }
+    }
+    fn detail(&self) -> Option<&SideDetailRaw> {
+        match self {
+            SideRaw::Index(_) => None,
+            SideRaw::Detail(detail) => Some(detail.as_ref()),
+        }
+    }
+    fn label(&self) -> Option<String> {
+        match self {
+            SideRaw::Index(_) => None,
+            SideRaw::Detail(detail) => detail.label.clone(),
+        }
+    }
+    fn name(&self) -> Option<Name> {
+        match self {
+            SideRaw::Index(_) => None,
+            SideRaw::Detail(detail) => detail.name.clone(),
+        }
+    }
+    fn berry_keys(&self) -> Vec<String> {
+        match self {
+            SideRaw::Index(_) => Vec::new(),
+            SideRaw::Detail(detail) => detail.strawberries.clone(),
+        }
+    }
+    fn checkpoint_rooms(&self) -> Vec<String> {
+        match self {
+            SideRaw::Index(_) => Vec::new(),
+            SideRaw::Detail(detail) => detail.checkpoints.clone(),
+        }
+    }
+    fn berry_kinds(&self) -> HashMap<String, BerryKind> {
+        let mut kinds = HashMap::new();
+        if let SideRaw::Detail(detail) = self {
+            for (keys, kind) in [
+                (&detail.goldens, BerryKind::Golden),
+                (&detail.winged_goldens, BerryKind::WingedGolden),
+                (&detail.moons, BerryKind::Moon),
+            ] {
+                for key in keys.iter() {
+                    kinds.insert(key.clone(), kind);
+                }
+            }
+        }
+        kinds
+    }
+}
+
+// the label of a side without one in maps.yaml: A, B, C, then D, E, ... and the number past Z
+pub fn side_label(side: usize) -> String {
+    match u8::try_from(side) {
+        Ok(side) if side < 26 => ((b'A' + side) as char).to_string(),
+        _ => side.to_string(),
+    }
+}
+
+// Languages tried for lang, from the most specific: "zh-TW" is zh-TW, zh, then en
+pub fn fallback_chain(lang: &str) -> Vec<String> {
+    let mut chain = vec![lang.to_string()];
+    let mut rest = lang;
+    while let Some((prefix, _)) = rest.rsplit_once('-') {
+        chain.push(prefix.to_string());
+        rest = prefix;
+    }
+    if !chain.iter().any(|l| l == "en") {
+        chain.push("en".to_string());
+    }
+    chain
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Name(BTreeMap<String, String>);
 
 impl Name {
-    pub fn get_name<'a>(&'a self) -> &'a str {
-        self.0.get("en").unwrap().as_str()
+    // (language, name) pairs, an empty name is kept as a blank to fill in
+    pub fn new<I: IntoIterator<Item=(String, String)>>(names: I) -> Self {
+        Name(names.into_iter().collect())
+    }
+    pub fn get(&self, lang: &str) -> Option<&str> {
+        self.0.get(lang).map(|n| n.as_str()).filter(|n| !n.is_empty())
+    }
+    // the first language of chain with a name
+    pub fn resolve<S: AsRef<str>>(&self, chain: &[S]) -> Option<&str> {
+        chain.iter().find_map(|lang| self.get(lang.as_ref()))
+    }
+    // English, or any name there is, None when there is none at all
+    pub fn any_name(&self) -> Option<&str> {
+        self.get("en").or_else(|| self.0.values().map(|n| n.as_str()).find(|n| !n.is_empty()))
     }
-    pub fn try_local_name<'a, 'b>(&'a self, lang: &'b str) -> &'a str {
-        match self.0.get(lang) {
-            None => self.get_name(),
-            Some(n) => n.as_str(),
+    // as any_name, but empty when there is no name. Used to panic without en.
+    pub fn get_name(&self) -> &str {
+        self.any_name().unwrap_or("")
+    }
+    // lang through its fallback chain, then as get_name
+    pub fn try_local_name(&self, lang: &str) -> &str {
+        self.resolve(&fallback_chain(lang)).unwrap_or_else(|| self.get_name())
+    }
+    // take the languages of other over these
+    fn patch(&mut self, other: &Name) {
+        for (lang, name) in other.0.iter() {
+            self.0.insert(lang.clone(), name.clone());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SideBuilder;
+
+    const MAPS: &str = "
+- level: 'SJ/1-Beginner'
+  name: 'SJ Beginner'
+  order: 2
+  maps:
+    - sid: 'SJ/1-Beginner/a'
+      name: { en: 'Forest Path', ja: '森の小径' }
+      authors: ['asteriskblue']
+      sides:
+        - side: 0
+          berries: 3
+          goldens: ['a-00:14']
+          moons: ['a-09:1']
+          checkpoints: ['cp1', 'cp2', 'cp3']
+        - side: 1
+          label: 'Remix'
+        - 3
+- level: 'Celeste'
+  name: 'Celeste'
+  order: 1
+  maps:
+    - sid: 'Celeste/1-ForsakenCity'
+      name: { en: 'Forsaken City', zh: '遗弃之城' }
+      sides: [0, 1, 2]
+    - sid: 'Celeste/Unnamed'
+      name: { en: '' }
+      sides: [0]
+";
+
+    fn maps() -> Vec<MapData> {
+        GameData::from_str(MAPS).unwrap().levels().flat_map(|level| level.maps()).collect()
+    }
+
+    fn map(sid: &str, side: usize) -> MapData {
+        maps().into_iter().find(|map_data| map_data.code == MapCode { sid: sid.to_string(), side }).unwrap()
+    }
+
+    #[test]
+    fn natural_order() {
+        let mut levels = vec!["10-Bonus", "2-Intermediate", "1-Beginner", "02-Zero", "b", "A"];
+        levels.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(levels, ["1-Beginner", "2-Intermediate", "02-Zero", "10-Bonus", "A", "b"]);
+        assert_eq!(natural_cmp("map9", "map10"), Ordering::Less);
+        assert_eq!(natural_cmp("a", "a"), Ordering::Equal);
+    }
+
+    #[test]
+    fn fallback() {
+        assert_eq!(fallback_chain("zh-TW"), ["zh-TW", "zh", "en"]);
+        assert_eq!(fallback_chain("ja"), ["ja", "en"]);
+        assert_eq!(fallback_chain("en"), ["en"]);
+    }
+
+    #[test]
+    fn side_labels() {
+        assert_eq!(side_label(0), "A");
+        assert_eq!(side_label(3), "D");
+        assert_eq!(side_label(25), "Z");
+        assert_eq!(side_label(26), "26");
+        assert_eq!(map("SJ/1-Beginner/a", 1).side_label(), "Remix");
+        assert_eq!(map("SJ/1-Beginner/a", 3).side_label(), "D");
+    }
+
+    #[test]
+    fn levels_in_order() {
+        let game_data = GameData::from_str(MAPS).unwrap();
+        let levels = game_data.levels().map(|level| level.level.as_str()).collect::<Vec<_>>();
+        assert_eq!(levels, ["Celeste", "SJ/1-Beginner"]);
+        assert_eq!(game_data.get_level_data("SJ/1-Beginner").unwrap().group(), "SJ");
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(map("SJ/1-Beginner/a", 0).try_local_name("ja"), "森の小径-A");
+        assert_eq!(map("SJ/1-Beginner/a", 1).try_local_name("ko"), "Forest Path-Remix");
+        assert_eq!(map("Celeste/1-ForsakenCity", 2).try_local_name("zh-TW"), "遗弃之城-C");
+        // a blank name is shown by its SID
+        assert_eq!(map("Celeste/Unnamed", 0).get_name(), "Celeste/Unnamed");
+        assert_eq!(map("SJ/1-Beginner/a", 0).credits().as_deref(), Some("by asteriskblue"));
+        assert_eq!(map("Celeste/1-ForsakenCity", 0).credits(), None);
+    }
+
+    #[test]
+    fn unnamed() {
+        assert_eq!(GameData::from_str(MAPS).unwrap().unnamed(), ["Celeste/Unnamed"]);
+        // deserialized directly it goes through from_levels as well
+        let game_data: GameData = serde_yaml::from_str(MAPS).unwrap();
+        assert_eq!(game_data.unnamed(), ["Celeste/Unnamed"]);
+    }
+
+    #[test]
+    fn berries() {
+        let map_data = map("SJ/1-Beginner/a", 0);
+        let stats = SideBuilder::new().berries(["a-01:1", "a-00:14", "a-09:1"]).build();
+        let counts = map_data.count_berries(&stats);
+        assert_eq!(counts, BerryCounts { normal: 1, golden: 1, winged_golden: 0, moon: 1 });
+        assert_eq!(counts.strawberries(), 2);
+        assert_eq!(counts.goldens(), 1);
+        assert_eq!(map_data.berry_total(), Some(3));
+        assert_eq!(map("SJ/1-Beginner/a", 1).berry_total(), None);
+    }
+
+    #[test]
+    fn furthest_checkpoint() {
+        let map_data = map("SJ/1-Beginner/a", 0);
+        let stats = SideBuilder::new().checkpoints(["cp3"]).build();
+        assert_eq!(map_data.furthest_checkpoint(&stats), Some(3));
+        assert_eq!(map_data.furthest_checkpoint(&SideBuilder::new().build()), None);
+        // without the order the number reached is taken
+        let stats = SideBuilder::new().checkpoints(["x", "y"]).build();
+        assert_eq!(map("Celeste/1-ForsakenCity", 0).furthest_checkpoint(&stats), Some(2));
+    }
+
+    #[test]
+    fn directory() {
+        let dir = std::env::temp_dir().join(format!("celeste_maps_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("1.yaml"), MAPS).unwrap();
+        std::fs::write(dir.join("2.yaml"), "- { level: 'list_x', name: 'list', tags: [list], maps: [{ sid: 'SJ/1-Beginner/a', name: { en: 'a' }, sides: [0] }] }").unwrap();
+        std::fs::write(dir.join("9.override.yaml"), "levels: [{ level: 'SJ/1-Beginner', name: 'SJ', order: 0 }]\nmaps: [{ sid: 'SJ/1-Beginner/a', name: { ja: '森の小道' } }]").unwrap();
+        let game_data = GameData::from_path(&dir).unwrap();
+        let first = game_data.levels().next().unwrap();
+        assert_eq!(first.name, "SJ");
+        assert_eq!(first.maps().next().unwrap().try_local_name("ja"), "森の小道-A");
+
+        std::fs::write(dir.join("3.yaml"), "- { level: 'other', name: 'o', maps: [{ sid: 'Celeste/Unnamed', name: { en: 'x' }, sides: [0] }] }").unwrap();
+        assert!(matches!(GameData::from_path(&dir), Err(Error::Conflict { .. })));
+        std::fs::remove_file(dir.join("3.yaml")).unwrap();
+
+        std::fs::write(dir.join("9.override.yaml"), "maps: [{ sid: 'SJ/typo', name: { ja: 'x' } }]").unwrap();
+        assert!(matches!(GameData::from_path(&dir), Err(Error::UnknownOverride { .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
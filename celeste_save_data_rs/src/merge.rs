@@ -0,0 +1,188 @@
+use crate::save_data::AreaModeStats;
+
+// How two SaveDatas are combined by SaveData::merge_with.
+// `right_is_newer` tells whether the right file has the later LastSave.
+pub trait MergeStrategy {
+    // counters such as deaths, jumps or time played
+    fn merge_count(&self, left: u64, right: u64, right_is_newer: bool) -> u64;
+    // the records of a side both files have played
+    fn merge_stats(&self, left: &mut AreaModeStats, right: AreaModeStats, right_is_newer: bool);
+}
+
+// Add up the counters, for files holding different mods.
+// This is what SaveData::merge does, and has always done: the records of a side
+// are only improved by a file that has completed it too.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sum;
+
+// Keep the larger counter and the best record, for overlapping backups of the same file.
+// Merging a file with itself changes nothing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Max;
+
+// Take everything from the file saved last.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreferNewest;
+
+impl MergeStrategy for Sum {
+    fn merge_count(&self, left: u64, right: u64, _right_is_newer: bool) -> u64 {
+        left + right
+    }
+    fn merge_stats(&self, left: &mut AreaModeStats, mut right: AreaModeStats, _right_is_newer: bool) {
+        // the completed side is the base
+        if !left.completed {
+            std::mem::swap(left, &mut right);
+        }
+        left.total_strawberries += right.total_strawberries;
+        left.heart_gem |= right.heart_gem;
+        left.assisted |= right.assisted;
+        left.deaths += right.deaths;
+        left.time_played += right.time_played;
+        left.strawberries.entity_id.extend(right.strawberries.entity_id);
+        left.checkpoints.rooms.extend(right.checkpoints.rooms);
+        if right.completed {
+            left.completed |= right.completed;
+            left.single_run_completed |= right.single_run_completed;
+            left.full_clear |= right.full_clear;
+            left.best_time = std::cmp::min(left.best_time, right.best_time);
+            left.best_full_clear_time = std::cmp::min(left.best_full_clear_time, right.best_full_clear_time);
+            left.best_dashes = std::cmp::min(left.best_dashes, right.best_dashes);
+            left.best_deaths = std::cmp::min(left.best_deaths, right.best_deaths);
+        }
+    }
+}
+
+impl MergeStrategy for Max {
+    fn merge_count(&self, left: u64, right: u64, _right_is_newer: bool) -> u64 {
+        std::cmp::max(left, right)
+    }
+    fn merge_stats(&self, left: &mut AreaModeStats, right: AreaModeStats, right_is_newer: bool) {
+        let deaths = self.merge_count(left.deaths, right.deaths, right_is_newer);
+        let time_played = self.merge_count(left.time_played.0, right.time_played.0, right_is_newer);
+        let total_strawberries = self.merge_count(left.total_strawberries, right.total_strawberries, right_is_newer);
+        left.merge_records(right);
+        left.deaths = deaths;
+        left.time_played.0 = time_played;
+        // never fewer than the berries both files have
+        left.total_strawberries = std::cmp::max(total_strawberries, left.strawberries.entity_id.len() as u64);
+    }
+}
+
+impl MergeStrategy for PreferNewest {
+    fn merge_count(&self, left: u64, right: u64, right_is_newer: bool) -> u64 {
+        if right_is_newer { right } else { left }
+    }
+    fn merge_stats(&self, left: &mut AreaModeStats, right: AreaModeStats, right_is_newer: bool) {
+        if right_is_newer {
+            *left = right;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{ SaveDataBuilder, AreaBuilder, SideBuilder };
+    use crate::save_data::{ SaveData, MapCode };
+    use crate::time::Time;
+
+    fn uncleared() -> AreaModeStats {
+        SideBuilder::new().deaths(10).time_played(Time(500)).berries(["a:1"]).checkpoints(["cp1"]).build()
+    }
+
+    fn cleared() -> AreaModeStats {
+        SideBuilder::new().cleared(Time(300), 2).best_dashes(5).deaths(4).time_played(Time(400)).berries(["a:1", "b:2"]).build()
+    }
+
+    #[test]
+    fn sum_adds_counters_and_keeps_the_completed_side() {
+        let mut left = uncleared();
+        Sum.merge_stats(&mut left, cleared(), false);
+        assert!(left.completed && left.single_run_completed);
+        assert_eq!(left.best_time, Time(300));
+        assert_eq!(left.best_deaths, 2);
+        assert_eq!(left.deaths, 14);
+        assert_eq!(left.time_played, Time(900));
+        assert_eq!(left.total_strawberries, 3);
+        assert_eq!(left.strawberries.entity_id.len(), 2);
+        assert_eq!(left.checkpoints_reached(), 1);
+    }
+
+    #[test]
+    fn sum_ignores_the_records_of_an_uncleared_side() {
+        let mut left = cleared();
+        // zeros of a side that was never completed are not a better record
+        Sum.merge_stats(&mut left, uncleared(), false);
+        assert_eq!(left.best_time, Time(300));
+        assert_eq!(left.best_dashes, 5);
+        assert_eq!(left.best_deaths, 2);
+    }
+
+    #[test]
+    fn max_is_idempotent() {
+        let mut left = cleared();
+        Max.merge_stats(&mut left, cleared(), false);
+        assert_eq!(left.deaths, 4);
+        assert_eq!(left.time_played, Time(400));
+        assert_eq!(left.total_strawberries, 2);
+        assert_eq!(left.best_time, Time(300));
+    }
+
+    #[test]
+    fn max_keeps_the_best_records() {
+        let mut left = uncleared();
+        let better = SideBuilder::new().cleared(Time(200), 1).deaths(3).build();
+        Max.merge_stats(&mut left, cleared(), false);
+        Max.merge_stats(&mut left, better, false);
+        assert!(left.single_run_completed);
+        assert_eq!(left.best_time, Time(200));
+        assert_eq!(left.best_deaths, 1);
+        assert_eq!(left.deaths, 10);
+        assert_eq!(left.total_strawberries, 2);
+        assert_eq!(left.checkpoints_reached(), 1);
+    }
+
+    #[test]
+    fn prefer_newest() {
+        let mut left = cleared();
+        PreferNewest.merge_stats(&mut left, uncleared(), false);
+        assert!(left.completed);
+        PreferNewest.merge_stats(&mut left, uncleared(), true);
+        assert!(!left.completed);
+        assert_eq!(PreferNewest.merge_count(1, 2, false), 1);
+        assert_eq!(PreferNewest.merge_count(1, 2, true), 2);
+    }
+
+    fn save(last_save: &str, deaths: u64, side: SideBuilder) -> SaveData {
+        SaveDataBuilder::new()
+            .last_save(last_save.parse().unwrap())
+            .total_deaths(deaths)
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new().side(0, side))
+            .build()
+    }
+
+    #[test]
+    fn merge_with_compares_last_save_in_utc() {
+        let code = MapCode { sid: "Celeste/1-ForsakenCity".to_string(), side: 0 };
+        // 18:00 in Japan is 09:00 UTC, an hour before the other file
+        let older = || save("2023-05-01T18:00:00+09:00", 10, SideBuilder::new().completed());
+        let newer = || save("2023-05-01T10:00:00Z", 20, SideBuilder::new());
+
+        let mut left = older();
+        left.merge_with(newer(), &PreferNewest);
+        assert_eq!(left.total_deaths, 20);
+        assert!(!left.map_stats[&code].completed);
+        assert_eq!(left.last_save, "2023-05-01T10:00:00Z".parse().unwrap());
+
+        let mut left = newer();
+        left.merge_with(older(), &PreferNewest);
+        assert_eq!(left.total_deaths, 20);
+        assert!(!left.map_stats[&code].completed);
+        assert_eq!(left.last_save, "2023-05-01T10:00:00Z".parse().unwrap());
+
+        let mut left = older();
+        left.merge(newer());
+        assert_eq!(left.total_deaths, 30);
+        assert!(left.map_stats[&code].completed);
+    }
+}
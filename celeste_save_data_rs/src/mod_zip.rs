@@ -0,0 +1,287 @@
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::io::{ Read, Seek };
+use serde::Deserialize;
+use crate::dialog::Dialog;
+use crate::map_data::{ LevelData, Name, natural_cmp };
+use crate::error::Error;
+
+// Dialog files of the game and the language key they go to in maps.yaml,
+// compared by dialog_language_key so that "Simplified Chinese.txt" or "english.txt" match too
+const DIALOG_LANGUAGES: &[(&str, &str)] = &[
+    ("English", "en"),
+    ("Japanese", "ja"),
+    ("Korean", "ko"),
+    ("SimplifiedChinese", "zh"),
+    ("Chinese", "zh"),
+    ("TraditionalChinese", "zh-TW"),
+    ("French", "fr"),
+    ("German", "de"),
+    ("Italian", "it"),
+    ("Spanish", "es"),
+    ("Russian", "ru"),
+    ("Brazilian", "pt-BR"),
+    ("BrazilianPortuguese", "pt-BR"),
+];
+
+// "Simplified Chinese" -> "simplifiedchinese"
+fn dialog_language_key(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+// suffixes Everest reads as the B- and C-side of the map without them
+const SIDE_SUFFIXES: [(&str, usize); 2] = [("-B", 1), ("-C", 2)];
+
+#[derive(Deserialize, Debug)]
+struct EverestModule {
+    #[serde(rename="Name")]
+    name: String,
+    #[serde(rename="Version", default)]
+    version: String,
+}
+
+// Foo.meta.yaml, only the modes are read
+#[derive(Deserialize, Debug, Default)]
+struct MapMeta {
+    #[serde(rename="Modes", default)]
+    modes: Vec<Option<MapMetaMode>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct MapMetaMode {
+    #[serde(rename="Path", default)]
+    path: Option<String>,
+}
+
+// What a mod archive registers: its name from everest.yaml and maps.yaml entries
+// for every level set under Maps/, named from its Dialog files.
+#[derive(Debug)]
+pub struct ModInfo {
+    pub name: String,
+    pub version: String,
+    pub levels: Vec<LevelData>,
+    // Dialog/English.txt and the other languages found
+    pub dialogs: HashMap<String, Dialog>,
+}
+
+fn zip_error(e: zip::result::ZipError) -> Error {
+    Error::BadArchive { message: e.to_string() }
+}
+
+impl ModInfo {
+    pub fn from_zip<R: Read + Seek>(reader: R) -> Result<Self, Error> {
+        let mut archive = zip::ZipArchive::new(reader).map_err(zip_error)?;
+        let mut files: HashMap<String, String> = HashMap::new();
+        let mut bins = Vec::new();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).map_err(zip_error)?;
+            // zips made on Windows may use backslashes
+            let path = file.name().replace('\\', "/");
+            let path = path.trim_start_matches("./").to_string();
+            if let Some(bin) = path.strip_prefix("Maps/").and_then(|p| p.strip_suffix(".bin")) {
+                bins.push(bin.to_string());
+                continue;
+            }
+            let wanted = path.eq_ignore_ascii_case("everest.yaml")
+                || path.eq_ignore_ascii_case("everest.yml")
+                || (path.starts_with("Dialog/") && path.ends_with(".txt"))
+                || (path.starts_with("Maps/") && path.ends_with(".meta.yaml"));
+            if wanted {
+                let mut text = String::new();
+                file.read_to_string(&mut text).map_err(|e| Error::BadArchive { message: format!("{}: {}", path, e) })?;
+                // files saved from Windows editors often start with a BOM
+                files.insert(path, text.trim_start_matches('\u{feff}').to_string());
+            }
+        }
+
+        let everest = files.iter()
+            .find(|(path, _)| path.eq_ignore_ascii_case("everest.yaml") || path.eq_ignore_ascii_case("everest.yml"))
+            .map(|(_, text)| text.as_str());
+        let (name, version) = match everest {
+            Some(text) => {
+                let modules: Vec<EverestModule> = serde_yaml::from_str(text)?;
+                let module = modules.into_iter().next().ok_or_else(|| Error::BadArchive { message: "everest.yaml has no module".to_string() })?;
+                (module.name, module.version)
+            }
+            None => return Err(Error::BadArchive { message: "no everest.yaml".to_string() }),
+        };
+
+        let mut dialog_files = files.iter()
+            .filter_map(|(path, text)| {
+                let stem = path.strip_prefix("Dialog/")?.strip_suffix(".txt")?;
+                Some((dialog_language_key(stem), path, text))
+            })
+            .collect::<Vec<_>>();
+        dialog_files.sort();
+        let mut dialogs = HashMap::new();
+        for (file_name, lang) in DIALOG_LANGUAGES {
+            let key = dialog_language_key(file_name);
+            for (_, _, text) in dialog_files.iter().filter(|(stem, _, _)| *stem == key) {
+                dialogs.entry(lang.to_string()).or_insert_with(Dialog::default).merge(Dialog::parse(text));
+            }
+        }
+
+        let sides = Self::sides(&bins, &files);
+        let mut levels: BTreeMap<&str, Vec<(&str, &Vec<usize>)>> = BTreeMap::new();
+        for (sid, sides) in sides.iter() {
+            // a map directly in Maps/ has no level set, Everest puts it in its own
+            let level = sid.rsplit_once('/').map(|(level, _)| level).unwrap_or(sid);
+            levels.entry(level).or_default().push((sid, sides));
+        }
+        let names_of = |key: &str| dialogs.iter()
+            .filter_map(|(lang, dialog)| dialog.map_name(key).map(|name| (lang.clone(), name)))
+            .collect::<BTreeMap<_, _>>();
+        let mut levels = levels.into_iter().map(|(level, mut maps)| {
+            maps.sort_by(|a, b| natural_cmp(a.0, b.0));
+            let level_names = names_of(level);
+            let level_name = level_names.get("en").or_else(|| level_names.values().next()).cloned().unwrap_or_else(|| level.to_string());
+            let mut level_data = LevelData::new(level, &level_name);
+            for (sid, sides) in maps {
+                let mut names = names_of(sid);
+                // leave a blank to fill in rather than an entry with no en
+                names.entry("en".to_string()).or_default();
+                level_data.push_map(sid, Name::new(names), sides.clone(), Some(name.clone()));
+            }
+            level_data
+        }).collect::<Vec<_>>();
+        levels.sort_by(|a, b| natural_cmp(&a.level, &b.level));
+        Ok(ModInfo { name, version, levels, dialogs })
+    }
+    // SID to its sides, from -B/-C bins next to the map and the Path of the modes in its meta.yaml.
+    // A bin some meta.yaml points to is a side of that map, not a map of its own.
+    fn sides(bins: &[String], files: &HashMap<String, String>) -> BTreeMap<String, Vec<usize>> {
+        // a broken meta.yaml only loses the sides it would add
+        let metas = bins.iter()
+            .filter_map(|bin| files.get(&format!("Maps/{}.meta.yaml", bin)).map(|text| (bin, serde_yaml::from_str::<MapMeta>(text).unwrap_or_default())))
+            .collect::<HashMap<_, _>>();
+        let mode_path = |mode: &Option<MapMetaMode>| {
+            let path = mode.as_ref()?.path.as_ref()?.replace('\\', "/");
+            let path = path.trim_start_matches("Maps/").trim_end_matches(".bin");
+            (!path.is_empty()).then(|| path.to_string())
+        };
+        let mode_targets = metas.iter()
+            .flat_map(|(bin, meta)| meta.modes.iter().skip(1).filter_map(mode_path).filter(move |path| path != *bin))
+            .collect::<HashSet<_>>();
+
+        let mut sides: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for bin in bins.iter().filter(|bin| !mode_targets.contains(*bin)) {
+            let side_of = SIDE_SUFFIXES.iter().find_map(|(suffix, side)| {
+                bin.strip_suffix(suffix).filter(|base| bins.iter().any(|b| b == base)).map(|base| (base, *side))
+            });
+            match side_of {
+                Some((base, side)) => sides.entry(base.to_string()).or_default().push(side),
+                None => sides.entry(bin.clone()).or_default().push(0),
+            }
+        }
+        for (sid, sides) in sides.iter_mut() {
+            let Some(meta) = metas.get(sid) else { continue };
+            for (side, mode) in meta.modes.iter().enumerate().skip(1) {
+                if mode_path(mode).is_some() && !sides.contains(&side) {
+                    sides.push(side);
+                }
+            }
+        }
+        sides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{ Cursor, Write };
+
+    fn archive(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, text) in files {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(text.as_bytes()).unwrap();
+        }
+        let mut buffer = writer.finish().unwrap();
+        buffer.set_position(0);
+        buffer
+    }
+
+    const EVEREST: (&str, &str) = ("everest.yaml", "- Name: TestMod\n  Version: 1.2.3\n");
+
+    fn sides(info: &ModInfo) -> Vec<(String, Vec<usize>)> {
+        info.levels.iter()
+            .flat_map(|level| level.maps())
+            .fold(Vec::new(), |mut sides: Vec<(String, Vec<usize>)>, map_data| {
+                match sides.iter_mut().find(|(sid, _)| *sid == map_data.code.sid) {
+                    Some((_, s)) => s.push(map_data.code.side),
+                    None => sides.push((map_data.code.sid, vec![map_data.code.side])),
+                }
+                sides
+            })
+    }
+
+    #[test]
+    fn side_suffixes() {
+        let info = ModInfo::from_zip(archive(&[
+            EVEREST,
+            ("Maps/Test/1-Map.bin", ""),
+            ("Maps/Test/1-Map-B.bin", ""),
+            ("Maps/Test/1-Map-C.bin", ""),
+            // no map without the suffix, so a map of its own
+            ("Maps/Test/2-Lonely-B.bin", ""),
+        ])).unwrap();
+        assert_eq!(info.name, "TestMod");
+        assert_eq!(info.version, "1.2.3");
+        assert_eq!(sides(&info), vec![
+            ("Test/1-Map".to_string(), vec![0, 1, 2]),
+            ("Test/2-Lonely-B".to_string(), vec![0]),
+        ]);
+    }
+
+    #[test]
+    fn meta_modes() {
+        let info = ModInfo::from_zip(archive(&[
+            EVEREST,
+            ("Maps/Test/a.bin", ""),
+            ("Maps/Test/a_remix.bin", ""),
+            ("Maps/Test/a.meta.yaml", "Modes:\n  - Path: ''\n  - Path: 'Test/a_remix'\n"),
+            ("Maps\\Test\\b.bin", ""),
+            ("Maps/Test/b.meta.yaml", "Modes:\n  -\n  -\n  - Path: 'Test/b'\n"),
+            ("Maps/Test/c.bin", ""),
+            ("Maps/Test/c.meta.yaml", "not: [yaml"),
+        ])).unwrap();
+        // a_remix is the B-side of a, b is its own C-side, and a broken meta.yaml adds nothing
+        assert_eq!(sides(&info), vec![
+            ("Test/a".to_string(), vec![0, 1]),
+            ("Test/b".to_string(), vec![0, 2]),
+            ("Test/c".to_string(), vec![0]),
+        ]);
+    }
+
+    #[test]
+    fn names_from_dialogs() {
+        let info = ModInfo::from_zip(archive(&[
+            EVEREST,
+            ("Maps/Test/1-Beginner/a.bin", ""),
+            ("Maps/Test/1-Beginner/b.bin", ""),
+            ("Dialog/English.txt", "\u{feff}Test_1_Beginner=\nBeginner Lobby\nTest_1_Beginner_a=\n{# ff0000}Forest{#} Path\n"),
+            ("Dialog/Simplified Chinese.txt", "test_1_beginner_a=森林小径\n"),
+            ("Dialog/brazilianportuguese.txt", "Test_1_Beginner_a=Caminho\n"),
+            ("Dialog/Unknown.txt", "Test_1_Beginner_a=?\n"),
+        ])).unwrap();
+        let mut langs = info.dialogs.keys().cloned().collect::<Vec<_>>();
+        langs.sort();
+        assert_eq!(langs, ["en", "pt-BR", "zh"]);
+        let level = &info.levels[0];
+        assert_eq!(level.level, "Test/1-Beginner");
+        assert_eq!(level.name, "Beginner Lobby");
+        let maps = level.maps().collect::<Vec<_>>();
+        assert_eq!(maps[0].try_local_name("en"), "Forest Path");
+        assert_eq!(maps[0].try_local_name("zh"), "森林小径");
+        assert_eq!(maps[0].try_local_name("pt-BR"), "Caminho");
+        assert_eq!(maps[0].mod_name.as_deref(), Some("TestMod"));
+        // no name anywhere leaves a blank en to fill in
+        assert_eq!(maps[1].name.get("en"), None);
+        assert_eq!(maps[1].get_name(), "Test/1-Beginner/b");
+    }
+
+    #[test]
+    fn no_everest_yaml() {
+        let result = ModInfo::from_zip(archive(&[("Maps/Test/a.bin", "")]));
+        assert!(matches!(result, Err(Error::BadArchive { .. })));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::save_data::SaveData;
+use crate::time::Time;
+use crate::error::Error;
+
+// What a file in Celeste's Saves directory holds, judged by its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveFileKind {
+    // 0.celeste
+    Save { slot: String },
+    // 0-modsave-CollabUtils2.celeste, written by Everest for each mod with save data
+    ModSave { slot: String, module: String },
+    // settings.celeste, modsettings-CollabUtils2.celeste
+    Settings,
+}
+
+impl SaveFileKind {
+    pub fn from_filename(filename: &str) -> Self {
+        let name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+        let stem = name.strip_suffix(".celeste").unwrap_or(name);
+        if stem == "settings" || stem.starts_with("modsettings-") {
+            SaveFileKind::Settings
+        }
+        else if let Some((slot, module)) = stem.split_once("-modsave-") {
+            SaveFileKind::ModSave { slot: slot.to_string(), module: module.to_string() }
+        }
+        else {
+            SaveFileKind::Save { slot: stem.to_string() }
+        }
+    }
+    pub fn slot(&self) -> Option<&str> {
+        match self {
+            SaveFileKind::Save { slot } | SaveFileKind::ModSave { slot, .. } => Some(slot),
+            SaveFileKind::Settings => None,
+        }
+    }
+}
+
+// 0-modsave-CollabUtils2.celeste, a yaml file. Only the keys read here, silver berries and
+// mini hearts are kept in the area stats of the main save like any other berry and heart.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct CollabSaveData {
+    // personal best of each speed berry, keyed by SID
+    #[serde(rename="SpeedBerryPBs")]
+    pub speed_berry_pbs: HashMap<String, Time>,
+    // lobby SID to the positions visited on its map, the positions are kept as is
+    #[serde(rename="VisitedLobbyPositions")]
+    pub visited_lobby_positions: HashMap<String, String>,
+}
+
+impl CollabSaveData {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(yml_str: &str) -> Result<Self, Error> {
+        // Everest writes an empty file when there is nothing to save
+        if yml_str.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_yaml::from_str(yml_str)?)
+    }
+    pub fn visited_lobbies(&self) -> impl Iterator<Item=&str> {
+        self.visited_lobby_positions.keys().map(|s| s.as_str())
+    }
+    pub(crate) fn merge(&mut self, right: Self) {
+        for (sid, time) in right.speed_berry_pbs.into_iter() {
+            let pb = self.speed_berry_pbs.entry(sid).or_insert(time);
+            *pb = std::cmp::min(*pb, time);
+        }
+        for (lobby, positions) in right.visited_lobby_positions.into_iter() {
+            self.visited_lobby_positions.entry(lobby).or_insert(positions);
+        }
+    }
+}
+
+impl SaveData {
+    // Parse a modsave file of this slot and keep what we understand.
+    // Modules other than CollabUtils2 are only recorded by name.
+    pub fn attach_mod_save(&mut self, module: &str, text: &str) -> Result<(), Error> {
+        if module == "CollabUtils2" {
+            let collab = CollabSaveData::from_str(text)?;
+            match self.collab.as_mut() {
+                Some(left) => left.merge(collab),
+                None => self.collab = Some(collab),
+            }
+        }
+        self.mod_saves.insert(module.to_string());
+        Ok(())
+    }
+}
+
+// Build one SaveData per slot from (filename, text) pairs, attaching each modsave file
+// to the save of its slot.
+pub fn load_save_files<'a, I>(files: I) -> Result<Vec<(String, SaveData)>, Error>
+    where I: IntoIterator<Item=(&'a str, &'a str)>,
+{
+    let mut slots: HashMap<String, SaveData> = HashMap::new();
+    let mut mod_saves = Vec::new();
+    for (filename, text) in files {
+        match SaveFileKind::from_filename(filename) {
+            SaveFileKind::Save { slot } => {
+                let data = SaveData::from_str(text)?;
+                match slots.get_mut(&slot) {
+                    Some(left) => left.merge(data),
+                    None => { slots.insert(slot, data); }
+                }
+            }
+            SaveFileKind::ModSave { slot, module } => mod_saves.push((slot, module, text)),
+            // settings hold no progress
+            SaveFileKind::Settings => {}
+        }
+    }
+    // a modsave file whose main save is missing still counts on its own
+    for (slot, module, text) in mod_saves {
+        slots.entry(slot).or_default().attach_mod_save(&module, text)?;
+    }
+    let mut slots = slots.into_iter().collect::<Vec<_>>();
+    slots.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(slots)
+}
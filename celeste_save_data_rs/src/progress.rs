@@ -0,0 +1,224 @@
+use crate::save_data::{ SaveData, AreaModeStats };
+use crate::map_data::{ GameData, LevelData, MapData };
+use crate::time::Time;
+
+// How far a side has been played, in the order the game unlocks them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapState {
+    Unplayed,
+    Played,
+    Completed,
+    FullCleared,
+}
+
+impl MapState {
+    pub fn of(stats: Option<&AreaModeStats>) -> Self {
+        match stats {
+            None => MapState::Unplayed,
+            Some(stats) if stats.full_clear => MapState::FullCleared,
+            Some(stats) if stats.completed => MapState::Completed,
+            Some(_) => MapState::Played,
+        }
+    }
+}
+
+// A map of maps.yaml with what the save file has on it
+#[derive(Debug)]
+pub struct ProgressRow<'a> {
+    pub level: &'a LevelData,
+    pub map: MapData,
+    pub stats: Option<&'a AreaModeStats>,
+    lang: &'a str,
+}
+
+impl<'a> ProgressRow<'a> {
+    pub fn state(&self) -> MapState {
+        MapState::of(self.stats)
+    }
+    // the map name in the language of the view, falling back to English
+    pub fn name(&self) -> String {
+        self.map.try_local_name(self.lang)
+    }
+}
+
+// Join of GameData and SaveData, every map of maps.yaml in order.
+//   ProgressView::new(&game_data, &save_data)
+//       .level("Celeste")
+//       .state(MapState::Completed)
+//       .author("asteriskblue")
+//       .named_in("ja")
+//       .rows()
+// Filters of the same kind are or-ed, different kinds are and-ed.
+#[derive(Debug, Clone)]
+pub struct ProgressView<'a> {
+    game_data: &'a GameData,
+    save_data: &'a SaveData,
+    levels: Vec<String>,
+    sides: Vec<usize>,
+    states: Vec<MapState>,
+    authors: Vec<String>,
+    named_in: Vec<String>,
+    lang: &'a str,
+}
+
+impl<'a> ProgressView<'a> {
+    pub fn new(game_data: &'a GameData, save_data: &'a SaveData) -> Self {
+        Self {
+            game_data,
+            save_data,
+            levels: Vec::new(),
+            sides: Vec::new(),
+            states: Vec::new(),
+            authors: Vec::new(),
+            named_in: Vec::new(),
+            lang: "en",
+        }
+    }
+    pub fn level(mut self, level: &str) -> Self {
+        self.levels.push(level.to_string());
+        self
+    }
+    pub fn side(mut self, side: usize) -> Self {
+        self.sides.push(side);
+        self
+    }
+    pub fn state(mut self, state: MapState) -> Self {
+        self.states.push(state);
+        self
+    }
+    // maps made by author, case-insensitive
+    pub fn author(mut self, author: &str) -> Self {
+        self.authors.push(author.to_string());
+        self
+    }
+    // maps that maps.yaml names in lang, e.g. to see what is left to translate.
+    // Unlike lang, this does not fall back to English.
+    pub fn named_in(mut self, lang: &str) -> Self {
+        self.named_in.push(lang.to_string());
+        self
+    }
+    // language of ProgressRow::name, it does not filter the rows
+    pub fn lang(mut self, lang: &'a str) -> Self {
+        self.lang = lang;
+        self
+    }
+    pub fn rows(&self) -> impl Iterator<Item=ProgressRow<'a>> + '_ {
+        self.game_data.levels()
+            .filter(|level| self.levels.is_empty() || self.levels.contains(&level.level))
+            .flat_map(move |level| level.maps().map(move |map| (level, map)))
+            .filter(|(_, map)| self.sides.is_empty() || self.sides.contains(&map.code.side))
+            .filter(|(_, map)| self.authors.is_empty() || self.authors.iter().any(|author| map.has_author(author)))
+            .filter(|(_, map)| self.named_in.is_empty() || self.named_in.iter().any(|lang| map.name.get(lang).is_some_and(|name| !name.trim().is_empty())))
+            .map(|(level, map)| {
+                let stats = self.save_data.map_stats.get(&map.code);
+                ProgressRow { level, map, stats, lang: self.lang }
+            })
+            .filter(|row| self.states.is_empty() || self.states.contains(&row.state()))
+    }
+    // the maps of the rows, as the chart functions take them
+    pub fn maps(&self) -> impl ExactSizeIterator<Item=MapData> {
+        self.rows().map(|row| row.map).collect::<Vec<_>>().into_iter()
+    }
+}
+
+// Totals over the rows of a ProgressView
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LevelProgress {
+    pub maps: usize,
+    pub cleared: usize,
+    pub full_clears: usize,
+    pub hearts: usize,
+    pub berries: usize,
+    // berries of the maps whose total is in maps.yaml, None when none is
+    pub berries_available: Option<usize>,
+    pub deaths: u64,
+    pub time: Time,
+}
+
+impl LevelProgress {
+    fn add(&mut self, row: &ProgressRow) {
+        self.maps += 1;
+        if let Some(total) = row.map.berry_total() {
+            *self.berries_available.get_or_insert(0) += total;
+        }
+        let Some(stats) = row.stats else { return };
+        if stats.completed {
+            self.cleared += 1;
+        }
+        if stats.full_clear {
+            self.full_clears += 1;
+        }
+        if stats.heart_gem {
+            self.hearts += 1;
+        }
+        // only maps with a total count, so the percent stays within berries_available
+        if let Some(total) = row.map.berry_total() {
+            self.berries += std::cmp::min(row.map.count_berries(stats).strawberries(), total);
+        }
+        self.deaths += stats.deaths;
+        self.time = Time(self.time.0 + stats.time_played.0);
+    }
+    fn percent(done: usize, total: usize) -> u64 {
+        (done * 100 / total) as u64
+    }
+    pub fn clear_percent(&self) -> Option<u64> {
+        (self.maps > 0).then(|| Self::percent(self.cleared, self.maps))
+    }
+    pub fn berry_percent(&self) -> Option<u64> {
+        self.berries_available.filter(|total| *total > 0).map(|total| Self::percent(std::cmp::min(self.berries, total), total))
+    }
+}
+
+// "14/20 clears, 62% berries", or "14/20 clears" when no map has a berry total
+impl std::fmt::Display for LevelProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}/{} clears", self.cleared, self.maps)?;
+        match self.berry_percent() {
+            Some(percent) => write!(f, ", {}% berries", percent),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> ProgressView<'a> {
+    // one summary over all the rows
+    pub fn summary(&self) -> LevelProgress {
+        let mut progress = LevelProgress::default();
+        for row in self.rows() {
+            progress.add(&row);
+        }
+        progress
+    }
+    // a summary for each author, most maps first. Names differing only in case are one author.
+    pub fn summaries_by_author(&self) -> Vec<(String, LevelProgress)> {
+        let mut summaries: Vec<(String, LevelProgress)> = Vec::new();
+        for row in self.rows() {
+            for author in row.map.authors.iter() {
+                match summaries.iter_mut().find(|(a, _)| a.eq_ignore_ascii_case(author)) {
+                    Some((_, progress)) => progress.add(&row),
+                    None => {
+                        let mut progress = LevelProgress::default();
+                        progress.add(&row);
+                        summaries.push((author.clone(), progress));
+                    }
+                }
+            }
+        }
+        summaries.sort_by(|a, b| b.1.maps.cmp(&a.1.maps).then_with(|| a.0.cmp(&b.0)));
+        summaries
+    }
+    // a summary for each level set, in the order of maps.yaml.
+    // Level sets with no rows are kept with an empty summary.
+    pub fn summaries(&self) -> Vec<(&'a LevelData, LevelProgress)> {
+        let mut summaries = self.game_data.levels()
+            .filter(|level| self.levels.is_empty() || self.levels.contains(&level.level))
+            .map(|level| (level, LevelProgress::default()))
+            .collect::<Vec<_>>();
+        for row in self.rows() {
+            if let Some((_, progress)) = summaries.iter_mut().find(|(level, _)| std::ptr::eq(*level, row.level)) {
+                progress.add(&row);
+            }
+        }
+        summaries
+    }
+}
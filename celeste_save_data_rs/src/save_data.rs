@@ -1,7 +1,10 @@
 use std::collections::{ HashSet, HashMap };
-use crate::time::Time;
+use crate::time::{ Time, Timestamp };
+use crate::error::Error;
+use crate::merge::{ MergeStrategy, Sum };
+use crate::modsave::CollabSaveData;
+use crate::writer::RootElement;
 use serde::{ Deserialize };
-use quick_xml::de::from_str;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all="PascalCase")]
@@ -9,18 +12,86 @@ pub struct SaveData {
     pub version: String,
     pub name: String,
     pub time: Time,
+    // files without LastSave, or with one that cannot be read, sort before any other
+    #[serde(default, deserialize_with="crate::time::timestamp_or_oldest")]
+    pub last_save: Timestamp,
     pub total_deaths: u64,
+    #[serde(default)]
+    pub total_strawberries: u64,
+    #[serde(default)]
+    pub total_golden_strawberries: u64,
     pub total_jumps: u64,
     pub total_wall_jumps: u64,
     pub total_dashes: u64,
+    #[serde(default)]
+    pub cheat_mode: bool,
+    #[serde(default)]
+    pub assist_mode: bool,
+    #[serde(default)]
+    pub variant_mode: bool,
+    #[serde(default)]
+    pub assists: Assists,
+    #[serde(default)]
+    pub unlocked_areas: u64,
+    #[serde(rename="Flags", default)]
+    raw_flags: Strings,
+    #[serde(rename="Poem", default)]
+    raw_poem: Strings,
+    #[serde(rename="SummitGems", default)]
+    raw_summit_gems: Booleans,
+    #[serde(rename="RevealedChapter9", default)]
+    pub revealed_chapter9: bool,
+    // the area selected on the map when the file was saved
+    #[serde(default)]
+    pub last_area: AreaKey,
+    #[serde(rename="LastArea_Safe", default)]
+    last_area_safe: Option<AreaKey>,
+    // story flags such as MetTheo
+    #[serde(skip)]
+    pub flags: HashSet<String>,
+    // IDs of the heart poems collected
+    #[serde(skip)]
+    pub poem: HashSet<String>,
+    // the six gems of The Summit
+    #[serde(skip)]
+    pub summit_gems: [bool; 6],
     areas: Areas,
     level_sets: LevelSets,
     #[serde(rename="LevelSetRecycleBin")]
     recycle_level_sets: LevelSets,
+    // the run in progress, Everest writes it as CurrentSession_Safe
+    #[serde(default)]
+    pub current_session: Option<Session>,
+    #[serde(rename="CurrentSession_Safe", default)]
+    current_session_safe: Option<Session>,
+    // Everest wrote n-modsave-*.celeste files next to this one
+    #[serde(default)]
+    pub has_modded_save_data: bool,
+    // from n-modsave-CollabUtils2.celeste, see SaveData::attach_mod_save
+    #[serde(skip)]
+    pub collab: Option<CollabSaveData>,
+    // modules whose modsave file has been attached
+    #[serde(skip)]
+    pub mod_saves: HashSet<String>,
     #[serde(skip)]
     pub map_stats: HashMap<MapCode, AreaModeStats>,
     #[serde(skip)]
     pub levels: HashMap<String, HashSet<MapCode>>,
+    // per-area (not per-side) records keyed by SID
+    #[serde(skip)]
+    pub area_info: HashMap<String, AreaInfo>,
+    // Everest level sets keyed by name, "Celeste" is not included
+    #[serde(skip)]
+    pub level_set_info: HashMap<String, LevelSetInfo>,
+    // the elements under <SaveData> in file order, so that to_xml can keep them
+    #[serde(skip)]
+    pub(crate) root_elements: Vec<RootElement>,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SaveData {
@@ -29,87 +100,436 @@ impl SaveData {
             version: String::new(),
             name: String::new(),
             time: Time(0),
+            last_save: Timestamp::default(),
             total_deaths: 0,
+            total_strawberries: 0,
+            total_golden_strawberries: 0,
             total_jumps: 0,
             total_wall_jumps: 0,
             total_dashes: 0,
+            cheat_mode: false,
+            assist_mode: false,
+            variant_mode: false,
+            assists: Assists::default(),
+            unlocked_areas: 0,
+            raw_flags: Strings::default(),
+            raw_poem: Strings::default(),
+            raw_summit_gems: Booleans::default(),
+            revealed_chapter9: false,
+            last_area: AreaKey::default(),
+            last_area_safe: None,
+            flags: HashSet::new(),
+            poem: HashSet::new(),
+            summit_gems: [false; 6],
             areas: Areas::default(),
             level_sets: LevelSets::default(),
             recycle_level_sets: LevelSets::default(),
+            current_session: None,
+            current_session_safe: None,
+            has_modded_save_data: false,
+            collab: None,
+            mod_saves: HashSet::new(),
             map_stats: HashMap::new(),
             levels: HashMap::new(),
+            area_info: HashMap::new(),
+            level_set_info: HashMap::new(),
+            root_elements: Vec::new(),
+        }
+    }
+    // records of this file may have been made with assists, variants or cheats
+    pub fn is_assisted(&self) -> bool {
+        self.cheat_mode || self.assist_mode || self.variant_mode
+    }
+    // add an area to the raw structure and count its berries in the totals, see SaveDataBuilder
+    pub(crate) fn push_area(&mut self, level_set: &str, id: u64, sid: &str, cassette: bool, modes: Vec<AreaModeStats>) {
+        let berries = modes.iter().map(|mode| mode.total_strawberries).sum::<u64>();
+        let area = AreaStats {
+            id,
+            cassette,
+            sid: sid.to_string(),
+            modes: Modes { area_mode_stats: modes },
+        };
+        if level_set == "Celeste" {
+            self.total_strawberries += berries;
+            // vanilla saves list the areas in ID order
+            let at = self.areas.area_stats.partition_point(|other| other.id <= id);
+            self.areas.area_stats.insert(at, area);
+            return;
+        }
+        if !self.level_sets.level_set_stats.iter().any(|level| level.name == level_set) {
+            self.level_sets.level_set_stats.push(LevelSetStats {
+                name: level_set.to_string(),
+                areas: Areas::default(),
+                poem: Strings::default(),
+                unlocked_areas: 0,
+                total_strawberries: 0,
+            });
+        }
+        if let Some(level) = self.level_sets.level_set_stats.iter_mut().find(|level| level.name == level_set) {
+            level.total_strawberries += berries;
+            level.areas.area_stats.push(area);
         }
     }
-    fn build_map_stats(&mut self) {
+    pub(crate) fn build_map_stats(&mut self) {
         self.map_stats.clear();
         self.levels.clear();
+        self.area_info.clear();
+        self.level_set_info.clear();
+        let assisted = self.is_assisted();
+        self.flags = self.raw_flags.items.iter().cloned().collect();
+        self.poem = self.raw_poem.items.iter().cloned().collect();
+        for (gem, collected) in self.summit_gems.iter_mut().zip(self.raw_summit_gems.items.iter()) {
+            *gem = *collected;
+        }
         for area in self.areas.area_stats.iter() {
+            self.area_info.insert(area.sid.clone(), AreaInfo::new(area, "Celeste"));
             for (i, mode) in area.modes.area_mode_stats.iter().enumerate() {
                 let code = MapCode {
                     sid: area.sid.clone(),
                     side: i,
                 };
-                self.levels.entry("Celeste".into()).or_insert(HashSet::new()).insert(code.clone());
-                self.map_stats.insert(code, mode.clone());
+                self.levels.entry("Celeste".into()).or_default().insert(code.clone());
+                self.map_stats.insert(code, AreaModeStats { assisted, ..mode.clone() });
             }
         }
         for level in self.level_sets.level_set_stats.iter() {
+            self.level_set_info.insert(level.name.clone(), LevelSetInfo::new(level, false));
             for area in level.areas.area_stats.iter() {
+                self.area_info.insert(area.sid.clone(), AreaInfo::new(area, &level.name));
                 for (i, mode) in area.modes.area_mode_stats.iter().enumerate() {
                     let code = MapCode {
                         sid: area.sid.clone(),
                         side: i,
                     };
-                    self.levels.entry(level.name.clone()).or_insert(HashSet::new()).insert(code.clone());
-                    self.map_stats.insert(code, mode.clone());
+                    self.levels.entry(level.name.clone()).or_default().insert(code.clone());
+                    self.map_stats.insert(code, AreaModeStats { assisted, ..mode.clone() });
                 }
             }
         }
         for level in self.recycle_level_sets.level_set_stats.iter() {
+            self.level_set_info.insert(level.name.clone(), LevelSetInfo::new(level, true));
             for area in level.areas.area_stats.iter() {
+                self.area_info.insert(area.sid.clone(), AreaInfo::new(area, &level.name));
                 for (i, mode) in area.modes.area_mode_stats.iter().enumerate() {
                     let code = MapCode {
                         sid: area.sid.clone(),
                         side: i,
                     };
-                    self.levels.entry(level.name.clone()).or_insert(HashSet::new()).insert(code.clone());
-                    self.map_stats.insert(code, mode.clone());
+                    self.levels.entry(level.name.clone()).or_default().insert(code.clone());
+                    self.map_stats.insert(code, AreaModeStats { assisted, ..mode.clone() });
+                }
+            }
+        }
+        if let Some(session) = self.current_session_safe.take() {
+            self.current_session = Some(session);
+        }
+        if let Some(last_area) = self.last_area_safe.take() {
+            self.last_area = last_area;
+        }
+        let area_info = &self.area_info;
+        let areas = self.current_session.as_mut().map(|s| &mut s.area).into_iter()
+            .chain(std::iter::once(&mut self.last_area));
+        for area in areas {
+            // vanilla saves only have the area ID
+            if area.sid.is_empty() {
+                if let Some((sid, _)) = area_info.iter().find(|(_, info)| info.level_set == "Celeste" && info.id == area.id) {
+                    area.sid = sid.clone();
                 }
             }
         }
     }
     // Create SaveData instance from xml string
-    pub fn from_str(xml_string: &str) -> Result<SaveData, String> {
-        let mut data: SaveData = from_str(xml_string).map_err(|e| format!("cannot load save data, \"{:?}\"", e))?;
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(xml_string: &str) -> Result<SaveData, Error> {
+        crate::check::check_save_xml(xml_string)?;
+        let mut data: SaveData = crate::check::deserialize_save_xml(xml_string)?;
         data.build_map_stats();
+        data.keep_root_elements(xml_string);
         Ok(data)
     }
     // merge two SaveDatas for those who are separating save data for multi mods
     pub fn merge(&mut self, right: SaveData) {
-        self.time += right.time;
-        self.total_deaths += right.total_deaths;
-        //self.total_strawberries += right.total_strawberries
-        //self.total_golden_strawberries += right.total_golden_strawberries
-        self.total_jumps += right.total_jumps;
-        self.total_wall_jumps += right.total_wall_jumps;
-        self.total_dashes += right.total_dashes;
+        self.merge_with(right, &Sum);
+    }
+    // merge two SaveDatas with the given policy, see crate::merge
+    pub fn merge_with(&mut self, right: SaveData, strategy: &dyn MergeStrategy) {
+        // merging into SaveData::new() takes over the file header
+        if self.version.is_empty() {
+            self.version = right.version.clone();
+            self.name = right.name.clone();
+            self.root_elements = right.root_elements.clone();
+        }
+        let newer = right.last_save > self.last_save;
+        let count = |left: u64, right: u64| strategy.merge_count(left, right, newer);
+        self.time.0 = count(self.time.0, right.time.0);
+        self.total_deaths = count(self.total_deaths, right.total_deaths);
+        self.total_strawberries = count(self.total_strawberries, right.total_strawberries);
+        self.total_golden_strawberries = count(self.total_golden_strawberries, right.total_golden_strawberries);
+        self.total_jumps = count(self.total_jumps, right.total_jumps);
+        self.total_wall_jumps = count(self.total_wall_jumps, right.total_wall_jumps);
+        self.total_dashes = count(self.total_dashes, right.total_dashes);
+        self.last_save = std::cmp::max(self.last_save, right.last_save);
+        self.cheat_mode |= right.cheat_mode;
+        self.assist_mode |= right.assist_mode;
+        self.variant_mode |= right.variant_mode;
+        self.assists.merge(&right.assists);
+        if right.current_session.is_some() && (newer || self.current_session.is_none()) {
+            self.current_session = right.current_session;
+        }
+        if newer {
+            self.last_area = right.last_area;
+        }
+        self.unlocked_areas = std::cmp::max(self.unlocked_areas, right.unlocked_areas);
+        self.flags.extend(right.flags);
+        self.poem.extend(right.poem);
+        for (gem, collected) in self.summit_gems.iter_mut().zip(right.summit_gems) {
+            *gem |= collected;
+        }
+        self.revealed_chapter9 |= right.revealed_chapter9;
+        self.has_modded_save_data |= right.has_modded_save_data;
+        if let Some(collab) = right.collab {
+            match self.collab.as_mut() {
+                Some(left) => left.merge(collab),
+                None => self.collab = Some(collab),
+            }
+        }
+        self.mod_saves.extend(right.mod_saves);
         for (code, stats) in right.map_stats.into_iter() {
             if let Some(left_stats) = self.map_stats.get_mut(&code) {
-                left_stats.merge(stats);
+                left_stats.merge(stats, strategy, newer);
             }
             else {
                 self.map_stats.insert(code, stats);
             }
         }
+        for (sid, info) in right.area_info.into_iter() {
+            if let Some(left_info) = self.area_info.get_mut(&sid) {
+                left_info.merge(info);
+            }
+            else {
+                self.area_info.insert(sid, info);
+            }
+        }
+        for (name, info) in right.level_set_info.into_iter() {
+            if let Some(left_info) = self.level_set_info.get_mut(&name) {
+                left_info.merge(info, strategy, newer);
+            }
+            else {
+                self.level_set_info.insert(name, info);
+            }
+        }
         right.levels.into_iter().for_each(|(k, v)| {
             let v2 = self.levels.entry(k.clone())
-                .or_insert(HashSet::new());
+                .or_default();
             *v2 = v2.union(&v).cloned().collect();
         });
     }
 
-    pub fn total_strawberries(&self) -> usize {
-        self.map_stats.values().map(|v| v.total_strawberries()).sum()
+    // (level set, SID) of every area as written in the file, before build_map_stats dedups them
+    pub(crate) fn raw_areas(&self) -> impl Iterator<Item=(&str, &str)> {
+        let vanilla = self.areas.area_stats.iter().map(|area| ("Celeste", area.sid.as_str()));
+        let level_sets = self.level_sets.level_set_stats.iter()
+            .chain(self.recycle_level_sets.level_set_stats.iter())
+            .flat_map(|level| level.areas.area_stats.iter().map(move |area| (level.name.as_str(), area.sid.as_str())));
+        vanilla.chain(level_sets)
+    }
+    // names of the level sets in file order, LevelSets first and then LevelSetRecycleBin
+    pub(crate) fn raw_level_sets(&self) -> impl Iterator<Item=&str> {
+        self.level_sets.level_set_stats.iter()
+            .chain(self.recycle_level_sets.level_set_stats.iter())
+            .map(|level| level.name.as_str())
+    }
+    // every berry in map_stats, goldens included, see GameData::count_berries
+    pub fn collected_entities(&self) -> usize {
+        self.map_stats.values().map(|v| v.collected_entities()).sum()
+    }
+}
+
+// quick-xml reads an enum from the element name, so go through the text instead
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(try_from="String")]
+pub enum DashMode {
+    #[default]
+    Normal,
+    Two,
+    Infinite,
+}
+
+impl TryFrom<String> for DashMode {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.as_str() {
+            "Normal" => Ok(DashMode::Normal),
+            "Two" => Ok(DashMode::Two),
+            "Infinite" => Ok(DashMode::Infinite),
+            _ => Err(format!("unknown dash mode `{}`", s)),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all="PascalCase", default)]
+pub struct Assists {
+    // 10 is 100%
+    pub game_speed: u64,
+    pub invincible: bool,
+    pub dash_mode: DashMode,
+    pub dash_assist: bool,
+    pub infinite_stamina: bool,
+    pub mirror_mode: bool,
+    pub three_sixty_dashing: bool,
+    pub invisible_motion: bool,
+    pub no_grabbing: bool,
+    pub low_friction: bool,
+    pub super_dashing: bool,
+    pub hiccups: bool,
+    pub play_as_badeline: bool,
+}
+
+impl Default for Assists {
+    fn default() -> Self {
+        Self {
+            game_speed: 10,
+            invincible: false,
+            dash_mode: DashMode::Normal,
+            dash_assist: false,
+            infinite_stamina: false,
+            mirror_mode: false,
+            three_sixty_dashing: false,
+            invisible_motion: false,
+            no_grabbing: false,
+            low_friction: false,
+            super_dashing: false,
+            hiccups: false,
+            play_as_badeline: false,
+        }
+    }
+}
+
+impl Assists {
+    // keep every assist that is enabled on either side
+    fn merge(&mut self, right: &Self) {
+        if self.game_speed == 10 {
+            self.game_speed = right.game_speed;
+        }
+        if self.dash_mode == DashMode::Normal {
+            self.dash_mode = right.dash_mode;
+        }
+        self.invincible |= right.invincible;
+        self.dash_assist |= right.dash_assist;
+        self.infinite_stamina |= right.infinite_stamina;
+        self.mirror_mode |= right.mirror_mode;
+        self.three_sixty_dashing |= right.three_sixty_dashing;
+        self.invisible_motion |= right.invisible_motion;
+        self.no_grabbing |= right.no_grabbing;
+        self.low_friction |= right.low_friction;
+        self.super_dashing |= right.super_dashing;
+        self.hiccups |= right.hiccups;
+        self.play_as_badeline |= right.play_as_badeline;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AreaInfo {
+    pub cassette: bool,
+    // the area ID the game assigned when the file was saved
+    pub id: u64,
+    pub level_set: String,
+    // the number of modes written for the area, sides that were never played included
+    pub modes: usize,
+}
+
+impl AreaInfo {
+    fn new(area: &AreaStats, level_set: &str) -> Self {
+        Self {
+            cassette: area.cassette,
+            id: area.id,
+            level_set: level_set.to_string(),
+            modes: area.modes.area_mode_stats.len(),
+        }
+    }
+    fn merge(&mut self, right: Self) {
+        self.cassette |= right.cassette;
+        self.modes = std::cmp::max(self.modes, right.modes);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LevelSetInfo {
+    // the level set is in LevelSetRecycleBin, i.e. the mod is not installed
+    pub recycled: bool,
+    pub unlocked_areas: u64,
+    pub total_strawberries: u64,
+    pub poem: Vec<String>,
+}
+
+impl LevelSetInfo {
+    fn new(level: &LevelSetStats, recycled: bool) -> Self {
+        Self {
+            recycled,
+            unlocked_areas: level.unlocked_areas,
+            total_strawberries: level.total_strawberries,
+            poem: level.poem.items.clone(),
+        }
+    }
+    fn merge(&mut self, right: Self, strategy: &dyn MergeStrategy, right_is_newer: bool) {
+        self.recycled &= right.recycled;
+        self.unlocked_areas = std::cmp::max(self.unlocked_areas, right.unlocked_areas);
+        self.total_strawberries = strategy.merge_count(self.total_strawberries, right.total_strawberries, right_is_newer);
+        for poem in right.poem {
+            if !self.poem.contains(&poem) {
+                self.poem.push(poem);
+            }
+        }
+    }
+}
+
+// The run in progress when the file was saved
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all="PascalCase", default)]
+pub struct Session {
+    pub area: AreaKey,
+    // the room the run resumes in
+    pub level: String,
+    // the checkpoint the run was started from, empty for the beginning
+    pub start_checkpoint: String,
+    // berries collected in this run so far
+    pub strawberries: Strawberries,
+    pub deaths: u64,
+    pub time: Time,
+    pub in_area: bool,
+    // the element as written in the file, to_xml writes it back as is
+    #[serde(skip)]
+    pub(crate) raw: String,
+}
+
+impl Session {
+    pub fn code(&self) -> MapCode {
+        MapCode {
+            sid: self.area.sid.clone(),
+            side: self.area.side(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct AreaKey {
+    #[serde(rename="@ID", default)]
+    pub id: u64,
+    // Normal, BSide or CSide
+    #[serde(rename="@Mode", default)]
+    pub mode: String,
+    #[serde(rename="@SID", default)]
+    pub sid: String,
+}
+
+impl AreaKey {
+    pub fn side(&self) -> usize {
+        match self.mode.as_str() {
+            "BSide" => 1,
+            "CSide" => 2,
+            _ => 0,
+        }
     }
 }
 
@@ -119,11 +539,12 @@ pub struct MapCode {
     pub side: usize,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Default, Clone)]
 #[serde(rename_all="PascalCase")]
 pub struct AreaModeStats {
-    //#[serde(rename="@TotalStrawberries")]
-    //pub total_strawberries: u64,
+    // the game's own count, see MapData::count_berries for the strawberries
+    #[serde(rename="@TotalStrawberries", default)]
+    pub total_strawberries: u64,
     #[serde(rename="@Completed")]
     pub completed: bool,
     #[serde(rename="@SingleRunCompleted")]
@@ -145,10 +566,15 @@ pub struct AreaModeStats {
     #[serde(rename="@HeartGem")]
     pub heart_gem: bool,
     pub strawberries: Strawberries,
+    #[serde(default)]
+    pub checkpoints: Checkpoints,
+    // set when the save file had assist, variant or cheat mode enabled
+    #[serde(skip)]
+    pub assisted: bool,
 }
 
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Default, Clone)]
 pub struct Strawberries {
     #[serde(default)]
     #[serde(rename="EntityID")]
@@ -156,6 +582,14 @@ pub struct Strawberries {
 }
 
 
+// room names of the checkpoints reached
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Checkpoints {
+    #[serde(default)]
+    #[serde(rename="string")]
+    pub rooms: HashSet<String>,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EntityID {
     #[serde(rename="@Key")]
@@ -163,26 +597,46 @@ pub struct EntityID {
 }
 
 impl AreaModeStats {
-    pub fn total_strawberries(&self) -> usize {
+    // every berry collected, goldens included, see MapData::count_berries
+    pub fn collected_entities(&self) -> usize {
         self.strawberries.entity_id.len()
     }
-    fn merge(&mut self, mut right: Self) {
-        //self.total_strawberries += right.total_strawberries;
-        if !self.completed {
-            std::mem::swap(self, &mut right);
-        }
+    pub fn checkpoints_reached(&self) -> usize {
+        self.checkpoints.rooms.len()
+    }
+    pub fn merge(&mut self, right: Self, strategy: &dyn MergeStrategy, right_is_newer: bool) {
+        strategy.merge_stats(self, right, right_is_newer);
+    }
+    // Keep every berry, checkpoint and clear, and the best records of both sides, for Max.
+    // Counters (deaths, time played and TotalStrawberries) are left to the MergeStrategy.
+    pub(crate) fn merge_records(&mut self, right: Self) {
         self.heart_gem |= right.heart_gem;
-        self.deaths += right.deaths;
-        self.time_played += right.time_played;
+        self.assisted |= right.assisted;
         self.strawberries.entity_id.extend(right.strawberries.entity_id);
-        if right.completed {
-            self.completed |= right.completed;
-            self.single_run_completed |= right.single_run_completed;
-            self.full_clear |= right.full_clear;
-            self.best_time = std::cmp::min(self.best_time, right.best_time);
-            self.best_full_clear_time = std::cmp::min(self.best_full_clear_time, right.best_full_clear_time);
-            self.best_dashes = std::cmp::min(self.best_dashes, right.best_dashes);
-            self.best_deaths = std::cmp::min(self.best_deaths, right.best_deaths);
+        self.checkpoints.rooms.extend(right.checkpoints.rooms);
+        self.completed |= right.completed;
+        // best time, dashes and deaths are only recorded on a run from the start
+        if right.single_run_completed {
+            if self.single_run_completed {
+                self.best_time = std::cmp::min(self.best_time, right.best_time);
+                self.best_dashes = std::cmp::min(self.best_dashes, right.best_dashes);
+                self.best_deaths = std::cmp::min(self.best_deaths, right.best_deaths);
+            }
+            else {
+                self.single_run_completed = true;
+                self.best_time = right.best_time;
+                self.best_dashes = right.best_dashes;
+                self.best_deaths = right.best_deaths;
+            }
+        }
+        if right.full_clear {
+            if self.full_clear {
+                self.best_full_clear_time = std::cmp::min(self.best_full_clear_time, right.best_full_clear_time);
+            }
+            else {
+                self.full_clear = true;
+                self.best_full_clear_time = right.best_full_clear_time;
+            }
         }
     }
 }
@@ -198,8 +652,10 @@ struct Areas {
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all="PascalCase")]
 struct AreaStats {
+    #[serde(rename="@ID", default)]
+    id: u64,
     #[serde(rename="@Cassette")]
-    casette: bool,
+    cassette: bool,
     #[serde(rename="@SID")]
     sid: String,
     modes: Modes
@@ -223,4 +679,26 @@ struct LevelSetStats {
     #[serde(rename="@Name")]
     name: String,
     areas: Areas,
+    #[serde(default)]
+    poem: Strings,
+    #[serde(default)]
+    unlocked_areas: u64,
+    #[serde(default)]
+    total_strawberries: u64,
+}
+
+// a list serialized as <string> elements
+#[derive(Deserialize, Debug, Default, Clone)]
+struct Strings {
+    #[serde(default)]
+    #[serde(rename="string")]
+    items: Vec<String>,
+}
+
+// a list serialized as <boolean> elements
+#[derive(Deserialize, Debug, Default, Clone)]
+struct Booleans {
+    #[serde(default)]
+    #[serde(rename="boolean")]
+    items: Vec<bool>,
 }
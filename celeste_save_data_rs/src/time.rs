@@ -1,21 +1,92 @@
-use serde::Deserialize;
+use serde::{ Deserialize, Deserializer, Serialize };
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+// .NET TimeSpan ticks, 10,000,000 per second
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy)]
 pub struct Time(pub u64);
 
+pub const TICKS_PER_SECOND: u64 = 10_000_000;
+// The game adds TimeSpan.FromSeconds(1/60) every frame, which .NET rounds to
+// whole milliseconds, so one frame is 17ms in file times
+pub const TICKS_PER_FRAME: u64 = 170_000;
 
-fn div_rem(a: u64, b: u64) -> (u64, u64) {
-    (a / b, a % b)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeFormat {
+    // 1:02:03.456, same as Display
+    #[default]
+    Full,
+    // 62:03.456, hours folded into minutes
+    Compact,
+    // 1:02:03.456, 2:03.456 or 3.456, leading zero fields dropped like a split timer
+    Speedrun,
+    // 3723f
+    Frames,
 }
 
-impl std::fmt::Display for Time {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl Time {
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Time)
+    }
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Time(self.0.saturating_sub(rhs.0))
+    }
+    // nearest whole frame
+    pub fn to_frames(&self) -> u64 {
+        (self.0 + TICKS_PER_FRAME / 2) / TICKS_PER_FRAME
+    }
+    pub fn from_frames(frames: u64) -> Self {
+        Time(frames * TICKS_PER_FRAME)
+    }
+    pub fn format(&self, style: TimeFormat) -> String {
         let v = self.0 / 10000u64;
         let (v, ms) = div_rem(v, 1000);
         let (v, s) = div_rem(v, 60);
         let (h, m) = div_rem(v, 60);
-        write!(f, "{}:{:02}:{:02}.{:03}", h, m, s, ms)?;
-        Ok(())
+        match style {
+            TimeFormat::Full => format!("{}:{:02}:{:02}.{:03}", h, m, s, ms),
+            TimeFormat::Compact => format!("{:02}:{:02}.{:03}", h * 60 + m, s, ms),
+            TimeFormat::Speedrun =>
+                if h > 0 { format!("{}:{:02}:{:02}.{:03}", h, m, s, ms) }
+                else if m > 0 { format!("{}:{:02}.{:03}", m, s, ms) }
+                else { format!("{}.{:03}", s, ms) },
+            TimeFormat::Frames => format!("{}f", self.to_frames()),
+        }
+    }
+}
+
+// Accepts "1:02:03.456", "2:03.456", "3.456", "3" and frame counts like "3723f"
+impl std::str::FromStr for Time {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("bad time `{}`", s);
+        let s = s.trim();
+        if let Some(frames) = s.strip_suffix('f') {
+            return frames.parse::<u64>().map(Time::from_frames).map_err(|_| err());
+        }
+        let (clock, fraction) = s.split_once('.').unwrap_or((s, ""));
+        // a fraction finer than a tick can't be represented
+        if fraction.len() > 7 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(err());
+        }
+        let fields = clock.split(':').map(|v| v.parse::<u64>().map_err(|_| err())).collect::<Result<Vec<_>, _>>()?;
+        if fields.is_empty() || fields.len() > 3 || fields[1..].iter().any(|v| *v >= 60) {
+            return Err(err());
+        }
+        let seconds = fields.iter().try_fold(0u64, |acc, v| acc.checked_mul(60)?.checked_add(*v)).ok_or_else(err)?;
+        let fraction_ticks = if fraction.is_empty() { 0 } else { format!("{:0<7}", fraction).parse::<u64>().map_err(|_| err())? };
+        seconds.checked_mul(TICKS_PER_SECOND)
+            .and_then(|t| t.checked_add(fraction_ticks))
+            .map(Time)
+            .ok_or_else(err)
+    }
+}
+
+fn div_rem(a: u64, b: u64) -> (u64, u64) {
+    (a / b, a % b)
+}
+
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.format(TimeFormat::Full))
     }
 }
 
@@ -30,6 +101,7 @@ impl std::ops::AddAssign for Time {
         self.0 += rhs.0
     }
 }
+// panics when rhs is larger, see checked_sub and saturating_sub
 impl std::ops::Sub for Time {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self::Output {
@@ -44,7 +116,7 @@ impl std::cmp::PartialEq for Time {
 impl std::cmp::Eq for Time {}
 impl std::cmp::PartialOrd for Time {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.partial_cmp(&other.0)
+        Some(self.cmp(other))
     }
 }
 impl std::cmp::Ord for Time {
@@ -52,3 +124,170 @@ impl std::cmp::Ord for Time {
         self.0.cmp(&other.0)
     }
 }
+
+// A point in time as written in LastSave, e.g. "2023-05-01T19:00:00.1234567+09:00",
+// kept as UTC so saves written in different time zones compare correctly
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from="String")]
+pub struct Timestamp {
+    // seconds since 1970-01-01T00:00:00Z
+    pub seconds: i64,
+    pub nanos: u32,
+}
+
+// days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + if m <= 2 { 1 } else { 0 }, m, d)
+}
+
+impl std::str::FromStr for Timestamp {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("bad timestamp `{}`", s);
+        let s = s.trim();
+        let (date, time) = s.split_once('T').ok_or_else(err)?;
+        let date = date.split('-').map(|v| v.parse::<i64>().map_err(|_| err())).collect::<Result<Vec<_>, _>>()?;
+        if date.len() != 3 {
+            return Err(err());
+        }
+        // everything after the seconds is the offset, no offset means UTC
+        let offset_start = time.find(['Z', '+', '-']).unwrap_or(time.len());
+        let (clock, offset) = time.split_at(offset_start);
+        let offset_seconds = match offset {
+            "" | "Z" => 0,
+            _ => {
+                let sign = if offset.starts_with('-') { -1 } else { 1 };
+                let (h, m) = offset[1..].split_once(':').ok_or_else(err)?;
+                let h = h.parse::<i64>().map_err(|_| err())?;
+                let m = m.parse::<i64>().map_err(|_| err())?;
+                sign * (h * 3600 + m * 60)
+            }
+        };
+        let (clock, fraction) = clock.split_once('.').unwrap_or((clock, ""));
+        let clock = clock.split(':').map(|v| v.parse::<i64>().map_err(|_| err())).collect::<Result<Vec<_>, _>>()?;
+        if clock.len() != 3 || fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(err());
+        }
+        let nanos = format!("{:0<9}", fraction).parse::<u32>().map_err(|_| err())?;
+        let days = days_from_civil(date[0], date[1], date[2]);
+        Ok(Timestamp {
+            seconds: days * 86400 + clock[0] * 3600 + clock[1] * 60 + clock[2] - offset_seconds,
+            nanos,
+        })
+    }
+}
+
+// LastSave of a save file, a malformed one is taken as missing rather than failing the whole file
+pub(crate) fn timestamp_or_oldest<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Ok(s.parse().unwrap_or_default())
+}
+
+impl TryFrom<String> for Timestamp {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (y, m, d) = civil_from_days(self.seconds.div_euclid(86400));
+        let secs = self.seconds.rem_euclid(86400);
+        // .NET writes 7 fractional digits
+        write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:07}Z",
+            y, m, d, secs / 3600, secs / 60 % 60, secs % 60, self.nanos / 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time() {
+        assert_eq!("1:02:03.456".parse::<Time>().unwrap(), Time(37234560000));
+        assert_eq!("2:03.456".parse::<Time>().unwrap(), Time(1234560000));
+        assert_eq!("3.456".parse::<Time>().unwrap(), Time(34560000));
+        assert_eq!("3".parse::<Time>().unwrap(), Time(30000000));
+        assert_eq!(" 0.0000001 ".parse::<Time>().unwrap(), Time(1));
+        assert_eq!("60f".parse::<Time>().unwrap(), Time(60 * TICKS_PER_FRAME));
+    }
+
+    #[test]
+    fn parse_bad_time() {
+        for s in ["", "1:60.000", "1:2:3:4", "1.23456789", "1.2a", "a", "-1", "f", "1.5f"] {
+            assert!(s.parse::<Time>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn format_time() {
+        let t: Time = "1:02:03.456".parse().unwrap();
+        assert_eq!(t.format(TimeFormat::Full), "1:02:03.456");
+        assert_eq!(t.to_string(), "1:02:03.456");
+        assert_eq!(t.format(TimeFormat::Compact), "62:03.456");
+        assert_eq!(t.format(TimeFormat::Speedrun), "1:02:03.456");
+        let t: Time = "2:03.456".parse().unwrap();
+        assert_eq!(t.format(TimeFormat::Speedrun), "2:03.456");
+        let t: Time = "3.456".parse().unwrap();
+        assert_eq!(t.format(TimeFormat::Speedrun), "3.456");
+        assert_eq!(t.format(TimeFormat::Compact), "00:03.456");
+    }
+
+    #[test]
+    fn frames() {
+        assert_eq!(Time::from_frames(3723).format(TimeFormat::Frames), "3723f");
+        // off by less than half a frame still rounds to the frame
+        assert_eq!(Time(60 * TICKS_PER_FRAME + TICKS_PER_FRAME / 2 - 1).to_frames(), 60);
+        assert_eq!(Time(60 * TICKS_PER_FRAME + TICKS_PER_FRAME / 2).to_frames(), 61);
+    }
+
+    #[test]
+    fn sub() {
+        assert_eq!(Time(5).checked_sub(Time(3)), Some(Time(2)));
+        assert_eq!(Time(3).checked_sub(Time(5)), None);
+        assert_eq!(Time(3).saturating_sub(Time(5)), Time(0));
+    }
+
+    #[test]
+    fn parse_timestamp() {
+        let t: Timestamp = "1970-01-01T00:00:00".parse().unwrap();
+        assert_eq!(t, Timestamp { seconds: 0, nanos: 0 });
+        let t: Timestamp = "2023-05-01T19:00:00.1234567+09:00".parse().unwrap();
+        assert_eq!(t, Timestamp { seconds: 1682935200, nanos: 123456700 });
+        assert_eq!(t.to_string(), "2023-05-01T10:00:00.1234567Z");
+        // the same moment written in another zone
+        let utc: Timestamp = "2023-05-01T10:00:00.1234567Z".parse().unwrap();
+        let west: Timestamp = "2023-04-30T22:00:00.1234567-12:00".parse().unwrap();
+        assert_eq!(t, utc);
+        assert_eq!(t, west);
+        let before: Timestamp = "1969-12-31T23:59:59Z".parse().unwrap();
+        assert_eq!(before.seconds, -1);
+        assert_eq!(before.to_string(), "1969-12-31T23:59:59.0000000Z");
+    }
+
+    #[test]
+    fn parse_bad_timestamp() {
+        for s in ["", "2023-05-01", "2023-05T19:00:00", "2023-05-01T19:00", "2023-05-01T19:00:00+09", "2023-05-01T19:00:00.x"] {
+            assert!(s.parse::<Timestamp>().is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::save_data::{ SaveData, MapCode };
+use crate::map_data::{ GameData, side_label };
+use crate::time::Time;
+
+// Something in a save file the game would not have written on its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    BestDeathsOverDeaths {
+        code: MapCode,
+        best_deaths: u64,
+        deaths: u64,
+    },
+    BestTimeOverTimePlayed {
+        code: MapCode,
+        best_time: Time,
+        time_played: Time,
+    },
+    FullClearWithoutCompleted {
+        code: MapCode,
+    },
+    // the berries listed per map do not add up to the TotalStrawberries of their level set
+    StrawberryTotalMismatch {
+        level_set: String,
+        counted: u64,
+        total: u64,
+    },
+    // the time played on maps is longer than the file has been played
+    MapTimeOverFileTime {
+        map_time: Time,
+        file_time: Time,
+    },
+    // the same SID in more than one place, only one of them is kept
+    DuplicateSid {
+        sid: String,
+        level_sets: Vec<String>,
+    },
+}
+
+fn map_name(code: &MapCode) -> String {
+    format!("{} ({})", code.sid, side_label(code.side))
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::BestDeathsOverDeaths { code, best_deaths, deaths } => {
+                write!(f, "{}: best deaths {} is more than the total deaths {}", map_name(code), best_deaths, deaths)
+            }
+            Warning::BestTimeOverTimePlayed { code, best_time, time_played } => {
+                write!(f, "{}: best time {} is longer than the time played {}", map_name(code), best_time, time_played)
+            }
+            Warning::FullClearWithoutCompleted { code } => {
+                write!(f, "{}: full cleared but not completed", map_name(code))
+            }
+            Warning::StrawberryTotalMismatch { level_set, counted, total } => {
+                write!(f, "{}: {} strawberries are listed but the total says {}", level_set, counted, total)
+            }
+            Warning::MapTimeOverFileTime { map_time, file_time } => {
+                write!(f, "time played on maps {} is longer than the file time {}", map_time, file_time)
+            }
+            Warning::DuplicateSid { sid, level_sets } => {
+                write!(f, "{} is saved more than once (in {})", sid, level_sets.join(", "))
+            }
+        }
+    }
+}
+
+impl SaveData {
+    // Look for values that contradict each other, as in corrupt or hand-edited files.
+    // Meant for a single file, merged SaveDatas lose the per-file totals.
+    // game_data tells the goldens apart, which TotalStrawberries does not count.
+    pub fn validate(&self, game_data: &GameData) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+
+        let mut codes = self.map_stats.keys().collect::<Vec<_>>();
+        codes.sort_by(|a, b| (&a.sid, a.side).cmp(&(&b.sid, b.side)));
+        for code in codes {
+            let stats = &self.map_stats[code];
+            if stats.single_run_completed && stats.best_deaths > stats.deaths {
+                warnings.push(Warning::BestDeathsOverDeaths {
+                    code: code.clone(),
+                    best_deaths: stats.best_deaths,
+                    deaths: stats.deaths,
+                });
+            }
+            if stats.single_run_completed && stats.best_time > stats.time_played {
+                warnings.push(Warning::BestTimeOverTimePlayed {
+                    code: code.clone(),
+                    best_time: stats.best_time,
+                    time_played: stats.time_played,
+                });
+            }
+            if stats.full_clear && !stats.completed {
+                warnings.push(Warning::FullClearWithoutCompleted { code: code.clone() });
+            }
+        }
+
+        let mut counted: HashMap<&str, u64> = HashMap::new();
+        for (code, stats) in self.map_stats.iter() {
+            let level_set = self.area_info.get(&code.sid).map(|info| info.level_set.as_str()).unwrap_or("Celeste");
+            *counted.entry(level_set).or_default() += game_data.count_berries(code, stats).strawberries() as u64;
+        }
+        let mut totals = vec![("Celeste".to_string(), self.total_strawberries)];
+        let mut level_sets = self.level_set_info.iter().collect::<Vec<_>>();
+        level_sets.sort_by(|a, b| a.0.cmp(b.0));
+        totals.extend(level_sets.into_iter().map(|(name, info)| (name.clone(), info.total_strawberries)));
+        for (level_set, total) in totals {
+            let counted = counted.get(level_set.as_str()).copied().unwrap_or(0);
+            if counted != total {
+                warnings.push(Warning::StrawberryTotalMismatch { level_set, counted, total });
+            }
+        }
+
+        let map_time = Time(self.map_stats.values().map(|stats| stats.time_played.0).sum());
+        if map_time > self.time {
+            warnings.push(Warning::MapTimeOverFileTime { map_time, file_time: self.time });
+        }
+
+        let mut places: HashMap<&str, Vec<String>> = HashMap::new();
+        for (level_set, sid) in self.raw_areas() {
+            places.entry(sid).or_default().push(level_set.to_string());
+        }
+        let mut duplicates = places.into_iter().filter(|(_, v)| v.len() > 1).collect::<Vec<_>>();
+        duplicates.sort();
+        for (sid, level_sets) in duplicates {
+            warnings.push(Warning::DuplicateSid { sid: sid.to_string(), level_sets });
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{ SaveDataBuilder, AreaBuilder, SideBuilder };
+
+    fn save_data() -> SaveData {
+        SaveDataBuilder::new()
+            .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new()
+                .side(0, SideBuilder::new().cleared(Time(100), 3).berries(["a:1"]))
+                .side(3, SideBuilder::new().completed()))
+            .area("SJ", "SJ/a", AreaBuilder::new().side(0, SideBuilder::new().berries(["b:2", "c:3"])))
+            .build()
+    }
+
+    fn code(sid: &str, side: usize) -> MapCode {
+        MapCode { sid: sid.to_string(), side }
+    }
+
+    fn game_data() -> GameData {
+        GameData::from_str("
+- level: 'SJ'
+  name: 'SJ'
+  maps:
+    - sid: 'SJ/a'
+      name: { en: 'a' }
+      sides:
+        - side: 0
+          goldens: ['c:3']
+").unwrap()
+    }
+
+    #[test]
+    fn built_files_are_valid() {
+        assert_eq!(save_data().validate(&GameData::default()), vec![]);
+    }
+
+    #[test]
+    fn contradictions() {
+        let mut save_data = save_data();
+        let stats = save_data.map_stats.get_mut(&code("Celeste/1-ForsakenCity", 0)).unwrap();
+        stats.deaths = 1;
+        stats.time_played = Time(50);
+        save_data.map_stats.get_mut(&code("Celeste/1-ForsakenCity", 3)).unwrap().completed = false;
+        save_data.map_stats.get_mut(&code("Celeste/1-ForsakenCity", 3)).unwrap().full_clear = true;
+        save_data.total_strawberries = 5;
+        save_data.time = Time(10);
+        let warnings = save_data.validate(&GameData::default());
+        assert_eq!(warnings, vec![
+            Warning::BestDeathsOverDeaths { code: code("Celeste/1-ForsakenCity", 0), best_deaths: 3, deaths: 1 },
+            Warning::BestTimeOverTimePlayed { code: code("Celeste/1-ForsakenCity", 0), best_time: Time(100), time_played: Time(50) },
+            Warning::FullClearWithoutCompleted { code: code("Celeste/1-ForsakenCity", 3) },
+            Warning::StrawberryTotalMismatch { level_set: "Celeste".to_string(), counted: 1, total: 5 },
+            Warning::MapTimeOverFileTime { map_time: Time(50), file_time: Time(10) },
+        ]);
+        // sides past C are labeled like maps.yaml does
+        assert_eq!(warnings[2].to_string(), "Celeste/1-ForsakenCity (D): full cleared but not completed");
+    }
+
+    #[test]
+    fn duplicate_sid() {
+        let save_data = SaveDataBuilder::new()
+            .area("SJ", "SJ/a", AreaBuilder::new())
+            .area("Other", "SJ/a", AreaBuilder::new())
+            .build();
+        assert_eq!(save_data.validate(&GameData::default()), vec![
+            Warning::DuplicateSid { sid: "SJ/a".to_string(), level_sets: vec!["SJ".to_string(), "Other".to_string()] },
+        ]);
+    }
+
+    #[test]
+    fn goldens_are_not_strawberries() {
+        let mut save_data = save_data();
+        // the builder counts every berry in TotalStrawberries
+        assert_eq!(save_data.validate(&game_data()), vec![
+            Warning::StrawberryTotalMismatch { level_set: "SJ".to_string(), counted: 1, total: 2 },
+        ]);
+        save_data.level_set_info.get_mut("SJ").unwrap().total_strawberries = 1;
+        assert_eq!(save_data.validate(&game_data()), vec![]);
+    }
+}
@@ -0,0 +1,503 @@
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::fmt::Write;
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use crate::save_data::{ SaveData, AreaModeStats, AreaKey, Assists, MapCode };
+
+// the game writes the A, B and C sides of vanilla areas
+const MIN_MODES: usize = 3;
+
+// elements under <SaveData> that to_xml writes from the fields, in the order the game writes them
+const WRITTEN: &[&str] = &[
+    "Version", "Name", "Time", "LastSave", "CheatMode", "AssistMode", "VariantMode", "Assists",
+    "UnlockedAreas", "TotalDeaths", "TotalStrawberries", "TotalGoldenStrawberries", "TotalJumps",
+    "TotalWallJumps", "TotalDashes", "Flags", "Poem", "SummitGems", "RevealedChapter9",
+    "LastArea", "LastArea_Safe", "Areas", "LevelSets", "LevelSetRecycleBin", "HasModdedSaveData",
+];
+
+// an element under <SaveData>, with its text when it is not in WRITTEN
+#[derive(Debug, Clone)]
+pub(crate) struct RootElement {
+    pub(crate) name: String,
+    pub(crate) raw: Option<String>,
+}
+
+// "CurrentSession" for "<CurrentSession xsi:nil=\"true\" />"
+fn tag_name(raw: &str) -> &str {
+    raw.trim_start_matches('<')
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("")
+}
+
+struct AreaOut<'a> {
+    id: u64,
+    sid: &'a str,
+    cassette: bool,
+    modes: Vec<Option<&'a AreaModeStats>>,
+}
+
+fn bool_str(b: bool) -> &'static str {
+    if b { "true" } else { "false" }
+}
+
+// level set of an area that was not read from a file, e.g. "Celeste/1-ForsakenCity" -> "Celeste"
+fn level_set_of_sid(sid: &str) -> String {
+    match sid.rfind('/') {
+        Some(i) => sid[..i].to_string(),
+        None => sid.to_string(),
+    }
+}
+
+fn write_element(out: &mut String, indent: usize, name: &str, value: &str) {
+    let _ = writeln!(out, "{:indent$}<{}>{}</{}>", "", name, escape(value), name, indent = indent);
+}
+
+fn write_list<'a, I>(out: &mut String, indent: usize, name: &str, item: &str, values: I)
+    where I: IntoIterator<Item=&'a str>,
+{
+    let values = values.into_iter().collect::<Vec<_>>();
+    if values.is_empty() {
+        let _ = writeln!(out, "{:indent$}<{} />", "", name, indent = indent);
+        return;
+    }
+    let _ = writeln!(out, "{:indent$}<{}>", "", name, indent = indent);
+    for value in values {
+        write_element(out, indent + 2, item, value);
+    }
+    let _ = writeln!(out, "{:indent$}</{}>", "", name, indent = indent);
+}
+
+fn sorted(values: &HashSet<String>) -> Vec<&str> {
+    let mut values = values.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    values.sort();
+    values
+}
+
+fn write_area_key(out: &mut String, name: &str, area: &AreaKey) {
+    let _ = write!(out, "  <{} ID=\"{}\" Mode=\"{}\"",
+        name,
+        area.id,
+        if area.mode.is_empty() { "Normal" } else { &area.mode });
+    // vanilla saves have no SID
+    if !area.sid.is_empty() {
+        let _ = write!(out, " SID=\"{}\"", escape(&area.sid));
+    }
+    out.push_str(" />\n");
+}
+
+fn write_assists(out: &mut String, assists: &Assists) {
+    out.push_str("  <Assists>\n");
+    write_element(out, 4, "GameSpeed", &assists.game_speed.to_string());
+    write_element(out, 4, "Invincible", bool_str(assists.invincible));
+    write_element(out, 4, "DashMode", &format!("{:?}", assists.dash_mode));
+    write_element(out, 4, "DashAssist", bool_str(assists.dash_assist));
+    write_element(out, 4, "InfiniteStamina", bool_str(assists.infinite_stamina));
+    write_element(out, 4, "MirrorMode", bool_str(assists.mirror_mode));
+    write_element(out, 4, "ThreeSixtyDashing", bool_str(assists.three_sixty_dashing));
+    write_element(out, 4, "InvisibleMotion", bool_str(assists.invisible_motion));
+    write_element(out, 4, "NoGrabbing", bool_str(assists.no_grabbing));
+    write_element(out, 4, "LowFriction", bool_str(assists.low_friction));
+    write_element(out, 4, "SuperDashing", bool_str(assists.super_dashing));
+    write_element(out, 4, "Hiccups", bool_str(assists.hiccups));
+    write_element(out, 4, "PlayAsBadeline", bool_str(assists.play_as_badeline));
+    out.push_str("  </Assists>\n");
+}
+
+fn write_mode(out: &mut String, indent: usize, stats: &AreaModeStats) {
+    let _ = writeln!(out,
+        "{:indent$}<AreaModeStats TotalStrawberries=\"{}\" Completed=\"{}\" SingleRunCompleted=\"{}\" FullClear=\"{}\" Deaths=\"{}\" TimePlayed=\"{}\" BestTime=\"{}\" BestFullClearTime=\"{}\" BestDashes=\"{}\" BestDeaths=\"{}\" HeartGem=\"{}\">",
+        "",
+        stats.total_strawberries,
+        bool_str(stats.completed),
+        bool_str(stats.single_run_completed),
+        bool_str(stats.full_clear),
+        stats.deaths,
+        stats.time_played.0,
+        stats.best_time.0,
+        stats.best_full_clear_time.0,
+        stats.best_dashes,
+        stats.best_deaths,
+        bool_str(stats.heart_gem),
+        indent = indent);
+    let mut keys = stats.strawberries.entity_id.iter().map(|e| e.key.as_str()).collect::<Vec<_>>();
+    keys.sort();
+    if keys.is_empty() {
+        let _ = writeln!(out, "{:indent$}<Strawberries />", "", indent = indent + 2);
+    }
+    else {
+        let _ = writeln!(out, "{:indent$}<Strawberries>", "", indent = indent + 2);
+        for key in keys {
+            let _ = writeln!(out, "{:indent$}<EntityID Key=\"{}\" />", "", escape(key), indent = indent + 4);
+        }
+        let _ = writeln!(out, "{:indent$}</Strawberries>", "", indent = indent + 2);
+    }
+    let mut rooms = stats.checkpoints.rooms.iter().collect::<Vec<_>>();
+    rooms.sort();
+    if rooms.is_empty() {
+        let _ = writeln!(out, "{:indent$}<Checkpoints />", "", indent = indent + 2);
+    }
+    else {
+        let _ = writeln!(out, "{:indent$}<Checkpoints>", "", indent = indent + 2);
+        for room in rooms {
+            write_element(out, indent + 4, "string", room);
+        }
+        let _ = writeln!(out, "{:indent$}</Checkpoints>", "", indent = indent + 2);
+    }
+    let _ = writeln!(out, "{:indent$}</AreaModeStats>", "", indent = indent);
+}
+
+fn write_areas(out: &mut String, indent: usize, areas: &[AreaOut]) {
+    if areas.is_empty() {
+        let _ = writeln!(out, "{:indent$}<Areas />", "", indent = indent);
+        return;
+    }
+    let _ = writeln!(out, "{:indent$}<Areas>", "", indent = indent);
+    let default_stats = AreaModeStats::default();
+    for area in areas {
+        let _ = writeln!(out, "{:indent$}<AreaStats ID=\"{}\" Cassette=\"{}\" SID=\"{}\">",
+            "", area.id, bool_str(area.cassette), escape(area.sid), indent = indent + 2);
+        let _ = writeln!(out, "{:indent$}<Modes>", "", indent = indent + 4);
+        for mode in area.modes.iter() {
+            write_mode(out, indent + 6, mode.unwrap_or(&default_stats));
+        }
+        let _ = writeln!(out, "{:indent$}</Modes>", "", indent = indent + 4);
+        let _ = writeln!(out, "{:indent$}</AreaStats>", "", indent = indent + 2);
+    }
+    let _ = writeln!(out, "{:indent$}</Areas>", "", indent = indent);
+}
+
+impl SaveData {
+    // group the flattened map_stats back into level sets and areas
+    fn areas_by_level_set(&self) -> BTreeMap<String, Vec<AreaOut<'_>>> {
+        let mut areas: HashMap<&str, AreaOut> = HashMap::new();
+        for sid in self.area_info.keys().chain(self.map_stats.keys().map(|code| &code.sid)) {
+            areas.entry(sid.as_str()).or_insert_with(|| {
+                let info = self.area_info.get(sid);
+                AreaOut {
+                    id: info.map(|i| i.id).unwrap_or(0),
+                    sid,
+                    cassette: info.map(|i| i.cassette).unwrap_or(false),
+                    modes: Vec::new(),
+                }
+            });
+        }
+        for area in areas.values_mut() {
+            let sides = self.map_stats.keys()
+                .filter(|code| code.sid == area.sid)
+                .map(|code| code.side + 1)
+                .max()
+                .unwrap_or(0);
+            // as many modes as the file had, or as the game writes for an area it did not have
+            let modes = match self.area_info.get(area.sid) {
+                Some(info) if info.modes > 0 => info.modes,
+                _ if level_set_of_sid(area.sid) == "Celeste" => MIN_MODES,
+                _ => 0,
+            };
+            area.modes = (0..std::cmp::max(sides, modes))
+                .map(|side| self.map_stats.get(&MapCode { sid: area.sid.to_string(), side }))
+                .collect();
+        }
+        let mut level_sets: BTreeMap<String, Vec<AreaOut>> = BTreeMap::new();
+        for (sid, area) in areas.into_iter() {
+            let level_set = match self.area_info.get(sid) {
+                Some(info) if !info.level_set.is_empty() => info.level_set.clone(),
+                _ => level_set_of_sid(sid),
+            };
+            level_sets.entry(level_set).or_default().push(area);
+        }
+        for areas in level_sets.values_mut() {
+            areas.sort_by(|a, b| (a.id, a.sid).cmp(&(b.id, b.sid)));
+        }
+        level_sets
+    }
+
+    // remember the elements under <SaveData> in file order, with the text of those
+    // to_xml does not write from the fields, and the text of the current session
+    pub(crate) fn keep_root_elements(&mut self, xml: &str) {
+        let mut reader = Reader::from_str(xml);
+        let mut depth = 0;
+        let mut start = 0;
+        self.root_elements.clear();
+        loop {
+            let offset = reader.buffer_position();
+            let name = match reader.read_event() {
+                Ok(Event::Start(_)) => {
+                    depth += 1;
+                    if depth == 2 {
+                        start = offset;
+                    }
+                    continue;
+                }
+                Ok(Event::End(e)) => {
+                    depth -= 1;
+                    if depth != 1 {
+                        continue;
+                    }
+                    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+                }
+                Ok(Event::Empty(e)) if depth == 1 => {
+                    start = offset;
+                    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => continue,
+            };
+            let raw = &xml[start..reader.buffer_position()];
+            self.root_elements.push(RootElement {
+                raw: (!WRITTEN.contains(&name.as_str())).then(|| raw.to_string()),
+                name,
+            });
+        }
+        // build_map_stats prefers the session Everest writes
+        let session = ["CurrentSession_Safe", "CurrentSession"].iter()
+            .find_map(|name| self.root_elements.iter().find(|e| e.name == *name))
+            .and_then(|e| e.raw.clone());
+        if let (Some(current), Some(raw)) = (self.current_session.as_mut(), session) {
+            current.raw = raw;
+        }
+    }
+
+    // the elements to_xml writes from the fields, in the order the game writes them
+    fn written_elements(&self) -> Vec<(&'static str, String)> {
+        let mut elements: Vec<(&'static str, String)> = Vec::new();
+        let mut add = |name: &'static str, write: &dyn Fn(&mut String)| {
+            let mut out = String::new();
+            write(&mut out);
+            elements.push((name, out));
+        };
+        add("Version", &|out| write_element(out, 2, "Version", &self.version));
+        add("Name", &|out| write_element(out, 2, "Name", &self.name));
+        add("Time", &|out| write_element(out, 2, "Time", &self.time.0.to_string()));
+        add("LastSave", &|out| write_element(out, 2, "LastSave", &self.last_save.to_string()));
+        add("CheatMode", &|out| write_element(out, 2, "CheatMode", bool_str(self.cheat_mode)));
+        add("AssistMode", &|out| write_element(out, 2, "AssistMode", bool_str(self.assist_mode)));
+        add("VariantMode", &|out| write_element(out, 2, "VariantMode", bool_str(self.variant_mode)));
+        add("Assists", &|out| write_assists(out, &self.assists));
+        add("UnlockedAreas", &|out| write_element(out, 2, "UnlockedAreas", &self.unlocked_areas.to_string()));
+        add("TotalDeaths", &|out| write_element(out, 2, "TotalDeaths", &self.total_deaths.to_string()));
+        add("TotalStrawberries", &|out| write_element(out, 2, "TotalStrawberries", &self.total_strawberries.to_string()));
+        add("TotalGoldenStrawberries", &|out| write_element(out, 2, "TotalGoldenStrawberries", &self.total_golden_strawberries.to_string()));
+        add("TotalJumps", &|out| write_element(out, 2, "TotalJumps", &self.total_jumps.to_string()));
+        add("TotalWallJumps", &|out| write_element(out, 2, "TotalWallJumps", &self.total_wall_jumps.to_string()));
+        add("TotalDashes", &|out| write_element(out, 2, "TotalDashes", &self.total_dashes.to_string()));
+        add("Flags", &|out| write_list(out, 2, "Flags", "string", sorted(&self.flags)));
+        add("Poem", &|out| write_list(out, 2, "Poem", "string", sorted(&self.poem)));
+        add("SummitGems", &|out| write_list(out, 2, "SummitGems", "boolean", self.summit_gems.iter().map(|g| bool_str(*g))));
+        add("RevealedChapter9", &|out| write_element(out, 2, "RevealedChapter9", bool_str(self.revealed_chapter9)));
+        add("LastArea", &|out| write_area_key(out, "LastArea", &self.last_area));
+        // build_map_stats took LastArea from LastArea_Safe, so it is only written back when the file had it
+        if self.root_elements.iter().any(|e| e.name == "LastArea_Safe") {
+            add("LastArea_Safe", &|out| write_area_key(out, "LastArea_Safe", &self.last_area));
+        }
+
+        let mut level_sets = self.areas_by_level_set();
+        let vanilla = level_sets.remove("Celeste").unwrap_or_default();
+        add("Areas", &|out| write_areas(out, 2, &vanilla));
+
+        // level sets only known from LevelSetStats still have to be written
+        for name in self.level_set_info.keys() {
+            level_sets.entry(name.clone()).or_default();
+        }
+        // level sets keep the order of the file, new ones go after them
+        let order = self.raw_level_sets().collect::<Vec<_>>();
+        let mut level_sets = level_sets.into_iter().collect::<Vec<_>>();
+        level_sets.sort_by_key(|(name, _)| (order.iter().position(|n| n == name).unwrap_or(order.len()), name.clone()));
+        let (recycled, installed): (Vec<_>, Vec<_>) = level_sets.into_iter()
+            .partition(|(name, _)| self.level_set_info.get(name).map(|i| i.recycled).unwrap_or(false));
+        for (element, level_sets) in [("LevelSets", installed), ("LevelSetRecycleBin", recycled)] {
+            add(element, &|out| {
+                if level_sets.is_empty() {
+                    let _ = writeln!(out, "  <{} />", element);
+                    return;
+                }
+                let _ = writeln!(out, "  <{}>", element);
+                for (name, areas) in level_sets.iter() {
+                    let info = self.level_set_info.get(name).cloned().unwrap_or_default();
+                    let _ = writeln!(out, "    <LevelSetStats Name=\"{}\">", escape(name));
+                    write_areas(out, 6, areas);
+                    write_list(out, 6, "Poem", "string", info.poem.iter().map(|s| s.as_str()));
+                    write_element(out, 6, "UnlockedAreas", &info.unlocked_areas.to_string());
+                    write_element(out, 6, "TotalStrawberries", &info.total_strawberries.to_string());
+                    out.push_str("    </LevelSetStats>\n");
+                }
+                let _ = writeln!(out, "  </{}>", element);
+            });
+        }
+        add("HasModdedSaveData", &|out| write_element(out, 2, "HasModdedSaveData", bool_str(self.has_modded_save_data)));
+        elements
+    }
+
+    // Serialize into the xml the game reads, so a merged or corrected SaveData can be saved as a .celeste file.
+    // Elements of the file the fields do not cover, such as CurrentSession, are written back as they were.
+    pub fn to_xml(&self) -> String {
+        let mut written = self.written_elements();
+        let mut elements: Vec<(String, String)> = Vec::new();
+        for element in self.root_elements.iter() {
+            match &element.raw {
+                Some(raw) => elements.push((element.name.clone(), format!("  {}\n", raw))),
+                None => {
+                    if let Some(i) = written.iter().position(|(name, _)| *name == element.name) {
+                        let (name, text) = written.remove(i);
+                        elements.push((name.to_string(), text));
+                    }
+                }
+            }
+        }
+        // elements the file did not have go after the ones the game writes before them
+        for (name, text) in written {
+            let before = &WRITTEN[..WRITTEN.iter().position(|n| *n == name).unwrap_or(0)];
+            let at = elements.iter().rposition(|(n, _)| before.contains(&n.as_str())).map(|i| i + 1).unwrap_or(0);
+            elements.insert(at, (name.to_string(), text));
+        }
+        // the session may come from another file after a merge
+        if let Some(session) = self.current_session.as_ref().filter(|s| !s.raw.is_empty()) {
+            let name = tag_name(&session.raw);
+            let text = format!("  {}\n", session.raw);
+            match elements.iter().position(|(n, _)| n == name) {
+                Some(i) => elements[i].1 = text,
+                None => {
+                    let at = elements.iter().position(|(n, _)| n == "HasModdedSaveData").unwrap_or(elements.len());
+                    elements.insert(at, (name.to_string(), text));
+                }
+            }
+        }
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<SaveData xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\n");
+        for (_, text) in elements {
+            out.push_str(&text);
+        }
+        out.push_str("</SaveData>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<SaveData xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+  <Version>1.4.0.0</Version>
+  <Name>Madeline</Name>
+  <Time>5000</Time>
+  <TotalDeaths>12</TotalDeaths>
+  <TotalStrawberries>2</TotalStrawberries>
+  <TotalJumps>40</TotalJumps>
+  <TotalWallJumps>3</TotalWallJumps>
+  <TotalDashes>20</TotalDashes>
+  <Flags>
+    <string>MetTheo</string>
+  </Flags>
+  <LastArea ID="0" Mode="Normal" />
+  <LastArea_Safe ID="11" Mode="Normal" SID="SJ/a" />
+  <CurrentSession_Safe Time="300" Deaths="2">
+    <Area ID="11" Mode="Normal" SID="SJ/a" />
+    <Level>b-01</Level>
+    <Strawberries>
+      <EntityID Key="a-01:3" />
+    </Strawberries>
+    <Dashes>4</Dashes>
+  </CurrentSession_Safe>
+  <Areas>
+    <AreaStats ID="1" Cassette="true" SID="Celeste/1-ForsakenCity">
+      <Modes>
+        <AreaModeStats TotalStrawberries="1" Completed="true" SingleRunCompleted="true" FullClear="false" Deaths="10" TimePlayed="3000" BestTime="2000" BestFullClearTime="0" BestDashes="3" BestDeaths="1" HeartGem="true">
+          <Strawberries>
+            <EntityID Key="s1:12" />
+          </Strawberries>
+          <Checkpoints />
+        </AreaModeStats>
+        <AreaModeStats TotalStrawberries="0" Completed="false" SingleRunCompleted="false" FullClear="false" Deaths="0" TimePlayed="0" BestTime="0" BestFullClearTime="0" BestDashes="0" BestDeaths="0" HeartGem="false">
+          <Strawberries />
+          <Checkpoints />
+        </AreaModeStats>
+        <AreaModeStats TotalStrawberries="0" Completed="false" SingleRunCompleted="false" FullClear="false" Deaths="0" TimePlayed="0" BestTime="0" BestFullClearTime="0" BestDashes="0" BestDeaths="0" HeartGem="false">
+          <Strawberries />
+          <Checkpoints />
+        </AreaModeStats>
+      </Modes>
+    </AreaStats>
+  </Areas>
+  <LevelSets>
+    <LevelSetStats Name="SJ">
+      <Areas>
+        <AreaStats ID="11" Cassette="false" SID="SJ/a">
+          <Modes>
+            <AreaModeStats TotalStrawberries="1" Completed="false" SingleRunCompleted="false" FullClear="false" Deaths="2" TimePlayed="2000" BestTime="0" BestFullClearTime="0" BestDashes="0" BestDeaths="0" HeartGem="false">
+              <Strawberries>
+                <EntityID Key="a-01:1" />
+              </Strawberries>
+              <Checkpoints>
+                <string>b-00</string>
+              </Checkpoints>
+            </AreaModeStats>
+          </Modes>
+        </AreaStats>
+      </Areas>
+      <Poem />
+      <UnlockedAreas>1</UnlockedAreas>
+      <TotalStrawberries>1</TotalStrawberries>
+    </LevelSetStats>
+    <LevelSetStats Name="Alpha">
+      <Areas />
+      <Poem />
+      <UnlockedAreas>0</UnlockedAreas>
+      <TotalStrawberries>0</TotalStrawberries>
+    </LevelSetStats>
+  </LevelSets>
+  <LevelSetRecycleBin />
+  <HasModdedSaveData>true</HasModdedSaveData>
+  <UnknownEverestField Value="kept">
+    <string>as is</string>
+  </UnknownEverestField>
+</SaveData>
+"#;
+
+    // what a SaveData says about its maps, in a comparable order
+    fn map_stats(save_data: &SaveData) -> Vec<String> {
+        let mut stats = save_data.map_stats.iter()
+            .map(|(code, stats)| {
+                let mut berries = stats.strawberries.entity_id.iter().map(|e| e.key.clone()).collect::<Vec<_>>();
+                berries.sort();
+                let mut rooms = stats.checkpoints.rooms.iter().cloned().collect::<Vec<_>>();
+                rooms.sort();
+                format!("{} {} deaths={} best={} {:?} {:?}", code.sid, code.side, stats.deaths, stats.best_time.0, berries, rooms)
+            })
+            .collect::<Vec<_>>();
+        stats.sort();
+        stats
+    }
+
+    #[test]
+    fn round_trip() {
+        let before = SaveData::from_str(SAVE).unwrap();
+        let xml = before.to_xml();
+        let after = SaveData::from_str(&xml).unwrap();
+        assert_eq!(map_stats(&after), map_stats(&before));
+        assert_eq!(after.raw_level_sets().collect::<Vec<_>>(), ["SJ", "Alpha"]);
+        let session = after.current_session.as_ref().unwrap();
+        assert_eq!(session.code(), MapCode { sid: "SJ/a".to_string(), side: 0 });
+        assert_eq!(session.level, "b-01");
+        assert_eq!(session.raw, before.current_session.as_ref().unwrap().raw);
+        assert_eq!(after.last_area.sid, "SJ/a");
+        // elements the fields do not cover are written as they were
+        assert!(xml.contains("    <Dashes>4</Dashes>\n"));
+        assert!(xml.contains("  <UnknownEverestField Value=\"kept\">\n    <string>as is</string>\n  </UnknownEverestField>\n"));
+        // areas keep their number of modes
+        assert_eq!(xml.matches("<AreaModeStats ").count(), 4);
+        assert_eq!(after.area_info["SJ/a"].modes, 1);
+        assert_eq!(after.to_xml(), xml);
+    }
+
+    #[test]
+    fn session_follows_a_merge() {
+        let mut merged = SaveData::new();
+        merged.merge(SaveData::from_str(SAVE).unwrap());
+        let xml = merged.to_xml();
+        assert!(xml.contains("  <CurrentSession_Safe Time=\"300\" Deaths=\"2\">\n"));
+        assert!(xml.contains("<UnknownEverestField"));
+    }
+}
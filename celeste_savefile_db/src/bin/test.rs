@@ -1,4 +1,4 @@
-use celeste_savefile_db::{ CelesteSavefileDB, Savefile };
+use celeste_savefile_db::{ CelesteSavefileDB, Savefile, SavefileKind };
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
@@ -6,12 +6,14 @@ async fn main() -> std::io::Result<()> {
     let sf1 = Savefile { 
         discord_id: "a".to_string(),
         filename: "1.celeste".to_string(),
-        xml: "hoge".to_string(),
+        kind: SavefileKind::Save,
+        content: "hoge".to_string(),
     };
     let sf2 = Savefile { 
         discord_id: "a".to_string(),
         filename: "1.celeste".to_string(),
-        xml: "nya".to_string(),
+        kind: SavefileKind::Save,
+        content: "nya".to_string(),
     };
     db.update_savefile(sf1).await.expect("cant update sf1");
     eprintln!("{:?}", db.get_savefiles("a").await.expect("cant get"));
@@ -1,7 +1,7 @@
 use futures::{AsyncWriteExt, AsyncReadExt};
 use futures::stream::TryStreamExt;
 use mongodb::{options::ClientOptions, Client, Database, Collection};
-use mongodb::bson::{ doc, oid::ObjectId };
+use mongodb::bson::{ doc, oid::ObjectId, Document };
 use mongodb::options::{ FindOneAndReplaceOptions, GridFsBucketOptions, GridFsUploadOptions };
 use mongodb::GridFsBucket;
 
@@ -11,11 +11,38 @@ pub struct CelesteSavefileDB {
     gridfs: GridFsBucket,
 }
 
+// what a Savefile holds: the game's xml save, or the yaml an Everest mod saves next to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SavefileKind {
+    Save,
+    ModSave,
+}
+
+impl SavefileKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SavefileKind::Save => "save",
+            SavefileKind::ModSave => "modsave",
+        }
+    }
+    // files uploaded before the kind was stored are told apart by their name
+    fn from_metadata(kind: Option<&str>, filename: &str) -> Self {
+        match kind {
+            Some("modsave") => SavefileKind::ModSave,
+            Some(_) => SavefileKind::Save,
+            None if filename.contains("-modsave-") => SavefileKind::ModSave,
+            None => SavefileKind::Save,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Savefile {
     pub discord_id: String,
     pub filename: String,
-    pub xml: String,
+    pub kind: SavefileKind,
+    // xml or yaml, see kind
+    pub content: String,
 }
 
 impl CelesteSavefileDB {
@@ -36,6 +63,25 @@ impl CelesteSavefileDB {
         })
     }
 
+    // Store the kind of the files uploaded before it was stored, so that they stop depending on
+    // the filename guess in SavefileKind::from_metadata. Returns the number of files updated.
+    pub async fn migrate_kinds(&self) -> Result<usize, String> {
+        let files = self.db.collection::<Document>("fs.files");
+        let mut cursor = files.find(doc!{ "metadata.kind": { "$exists": false } }, None)
+            .await.map_err(|e| format!("cant find {:?}", e))?;
+        let mut migrated = 0;
+        while let Some(file) = cursor.try_next()
+            .await.map_err(|e| format!("cant try next {:?}", e))? {
+                let filename = file.get_document("metadata").and_then(|m| m.get_str("filename"))
+                    .map_err(|e| format!("no filename {:?}", e))?;
+                let kind = SavefileKind::from_metadata(None, filename);
+                files.update_one(doc!{ "_id": file.get("_id") }, doc!{ "$set": { "metadata.kind": kind.as_str() } }, None)
+                    .await.map_err(|e| format!("cant update {:?}", e))?;
+                migrated += 1;
+        }
+        Ok(migrated)
+    }
+
     pub async fn debug_all_savefiles(&self) -> Result<(), String> {
         let mut cursor = self.gridfs.find(doc!{}, None)
             .await.map_err(|e| format!("cant find {:?}", e))?;
@@ -64,13 +110,16 @@ impl CelesteSavefileDB {
             .await.map_err(|e| format!("cant try next {:?}", e))? {
                 let mut stream = self.gridfs.open_download_stream(result.id)
                     .await.map_err(|e| format!("cant open download stream {:?}", e))?;
-                let mut xml = Vec::new();
-                let _ = stream.read_to_end(&mut xml)
+                let mut content = Vec::new();
+                let _ = stream.read_to_end(&mut content)
                     .await.map_err(|e| format!("fail read_to_end {:?}", e))?;
+                let metadata = result.metadata.unwrap();
+                let filename = metadata.get_str("filename").unwrap().to_string();
                 savefiles.push(Savefile {
                     discord_id: discord_id.to_string(),
-                    filename: result.metadata.unwrap().get_str("filename").unwrap().to_string(),
-                    xml: String::from_utf8(xml.to_vec()).unwrap(),
+                    kind: SavefileKind::from_metadata(metadata.get_str("kind").ok(), &filename),
+                    filename,
+                    content: String::from_utf8(content.to_vec()).unwrap(),
                 });
         }
         Ok(savefiles)
@@ -90,10 +139,10 @@ impl CelesteSavefileDB {
         }
         {
             let opts = GridFsUploadOptions::builder()
-                .metadata( Some(doc!{ "discord_id": savefile.discord_id, "filename": savefile.filename }) )
+                .metadata( Some(doc!{ "discord_id": savefile.discord_id, "filename": savefile.filename, "kind": savefile.kind.as_str() }) )
                 .build();
             let mut stream = self.gridfs.open_upload_stream(fsname, opts);
-            stream.write_all(savefile.xml.as_bytes()).await
+            stream.write_all(savefile.content.as_bytes()).await
                 .map_err(|e| format!("write_all error {:?}", e))?;
             stream.close().await.map_err(|e| format!("cant close stream {:?}", e))?;
         }
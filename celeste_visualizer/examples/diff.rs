@@ -1,13 +1,20 @@
 use celeste_visualizer::diff::{ diff_svg_chart };
-use celeste_save_data_rs::save_data::SaveData;
+use celeste_save_data_rs::builder::{ SaveDataBuilder, AreaBuilder, SideBuilder };
 use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::time::Time;
 
 fn main() {
-    //let xml = std::fs::read_to_string("../celeste_save_data_rs/0.celeste").unwrap();
-    let xml = std::fs::read_to_string("0.celeste").unwrap();
-    let before = SaveData::from_str(&xml).unwrap();
-    let xml = std::fs::read_to_string("1.celeste").unwrap();
-    let after = SaveData::from_str(&xml).unwrap();
+    let forsaken_city = AreaBuilder::new()
+        .side(0, SideBuilder::new().cleared(Time::from_frames(9000), 12));
+    let before = SaveDataBuilder::new()
+        .area("Celeste", "Celeste/1-ForsakenCity", forsaken_city.clone())
+        .build();
+    let after = SaveDataBuilder::new()
+        .area("Celeste", "Celeste/1-ForsakenCity", forsaken_city
+            .side(1, SideBuilder::new().cleared(Time::from_frames(6000), 30).heart_gem()))
+        .area("Celeste", "Celeste/2-OldSite", AreaBuilder::new()
+            .side(0, SideBuilder::new().cleared(Time::from_frames(12000), 25)))
+        .build();
 
     let yml = std::fs::read_to_string("../maps.yaml").unwrap();
     let game_data: GameData = GameData::from_str(&yml).unwrap();
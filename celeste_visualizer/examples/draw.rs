@@ -1,11 +1,17 @@
 use celeste_visualizer::{ generate_png, generate_svg_str };
-use celeste_save_data_rs::save_data::SaveData;
+use celeste_save_data_rs::builder::{ SaveDataBuilder, AreaBuilder, SideBuilder };
 use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::time::Time;
 
 fn main() {
-    //let xml = std::fs::read_to_string("../celeste_save_data_rs/0.celeste").unwrap();
-    let xml = std::fs::read_to_string("../../Downloads/hir.celeste").unwrap();
-    let save_data = SaveData::from_str(&xml).unwrap();
+    let save_data = SaveDataBuilder::new()
+        .area("Celeste", "Celeste/1-ForsakenCity", AreaBuilder::new()
+            .cassette()
+            .side(0, SideBuilder::new().cleared(Time::from_frames(9000), 12).full_clear(Time::from_frames(15000)).heart_gem())
+            .side(1, SideBuilder::new().cleared(Time::from_frames(6000), 30).heart_gem()))
+        .area("Celeste", "Celeste/2-OldSite", AreaBuilder::new()
+            .side(0, SideBuilder::new().cleared(Time::from_frames(12000), 25).deaths(40)))
+        .build();
     let yml = std::fs::read_to_string("../maps.yaml").unwrap();
     let game_data: GameData = GameData::from_str(&yml).unwrap();
     let chart = generate_svg_str(&save_data, game_data.get_level_data("Celeste").unwrap().maps(), "en");
@@ -1,5 +1,6 @@
 use celeste_save_data_rs::save_data::{ SaveData, MapCode };
 use celeste_save_data_rs::map_data::GameData;
+use celeste_save_data_rs::progress::ProgressView;
 use std::collections::HashMap;
 
 struct SaveDataDiff {
@@ -24,100 +25,85 @@ impl SaveDataDiff {
     }
     fn create_diff(game_data: &GameData, before: &SaveData, after: &SaveData) -> Self {
         let mut diff = Self::new();
-        for level in game_data.levels() {
-            for map_data in game_data.get_level_data(level.level.as_str()).unwrap().maps() {
-                let stats_diff = match (before.map_stats.get(&map_data.code), after.map_stats.get(&map_data.code)) {
-                    (None, None) => StatsDiff::Same,
-                    (Some(_), None) => StatsDiff::BeforeOnly,
-                    (None, Some(_)) => StatsDiff::AfterOnly,
-                    (Some(before), Some(after)) => {
-                        let strawberries = {
-                            let b = before.total_strawberries();
-                            let a = after.total_strawberries();
+        for row in ProgressView::new(game_data, after).rows() {
+            let map_data = row.map;
+            let stats_diff = match (before.map_stats.get(&map_data.code), row.stats) {
+                (None, None) => StatsDiff::Same,
+                (Some(_), None) => StatsDiff::BeforeOnly,
+                (None, Some(_)) => StatsDiff::AfterOnly,
+                (Some(before), Some(after)) => {
+                    let strawberries = {
+                        let b = map_data.count_berries(before);
+                        let a = map_data.count_berries(after);
+                        let sb = {
+                            let b = b.strawberries();
+                            let a = a.strawberries();
                             if b == a { DiffParam::Same }
                             else if b < a { DiffParam::Normal(format!("+{}", a - b)) }
                             else { DiffParam::Outlier(format!("-{}", b - a)) }
                         };
-                        let best_deaths = {
-                            let bsr = before.single_run_completed;
-                            let asr = after.single_run_completed;
-                            if bsr && asr {
-                                let b = before.best_deaths;
-                                let a = after.best_deaths;
-                                if b == a { DiffParam::Same }
-                                else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
-                                else { DiffParam::Outlier(format!("+{}", a - b)) }
-                            }
-                            else if !bsr && !asr {
-                                DiffParam::Same
-                            }
-                            else if !bsr && asr {
-                                DiffParam::Normal("new".to_string())
-                            }
-                            else {
-                                DiffParam::Outlier("degrate".to_string())
-                            }
-                        };
-                        let deaths = {
-                            let b = before.deaths;
-                            let a = after.deaths;
+                        let golden = {
+                            let b = b.goldens();
+                            let a = a.goldens();
                             if b == a { DiffParam::Same }
-                            else if b < a { DiffParam::Normal(format!("+{}", a - b)) }
-                            else { DiffParam::Outlier(format!("-{}", b - a)) }
+                            else if b < a { DiffParam::Normal(format!("+{}G", a - b)) }
+                            else { DiffParam::Outlier(format!("-{}G", b - a)) }
                         };
-                        let clr = {
-                            let bsr = before.single_run_completed;
-                            let asr = after.single_run_completed;
+                        sb.join(golden)
+                    };
+                    let best_deaths = {
+                        let bsr = before.single_run_completed;
+                        let asr = after.single_run_completed;
+                        if bsr && asr {
+                            let b = before.best_deaths;
+                            let a = after.best_deaths;
+                            if b == a { DiffParam::Same }
+                            else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
+                            else { DiffParam::Outlier(format!("+{}", a - b)) }
+                        }
+                        else if !bsr && !asr {
+                            DiffParam::Same
+                        }
+                        else if !bsr && asr {
+                            DiffParam::Normal("new".to_string())
+                        }
+                        else {
+                            DiffParam::Outlier("degrate".to_string())
+                        }
+                    };
+                    let deaths = {
+                        let b = before.deaths;
+                        let a = after.deaths;
+                        if b == a { DiffParam::Same }
+                        else if b < a { DiffParam::Normal(format!("+{}", a - b)) }
+                        else { DiffParam::Outlier(format!("-{}", b - a)) }
+                    };
+                    let clr = {
+                        let bsr = before.single_run_completed;
+                        let asr = after.single_run_completed;
+                        if bsr && asr {
+                            let b = before.best_time;
+                            let a = after.best_time;
+                            if b == a { DiffParam::Same }
+                            else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
+                            else { DiffParam::Outlier(format!("+{}", a - b)) }
+                        }
+                        else if !bsr && !asr {
+                            let bsr = before.completed;
+                            let asr = after.completed;
                             if bsr && asr {
-                                let b = before.best_time;
-                                let a = after.best_time;
+                                let b = before.time_played;
+                                let a = after.time_played;
                                 if b == a { DiffParam::Same }
                                 else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
                                 else { DiffParam::Outlier(format!("+{}", a - b)) }
                             }
                             else if !bsr && !asr {
-                                let bsr = before.completed;
-                                let asr = after.completed;
-                                if bsr && asr {
-                                    let b = before.time_played;
-                                    let a = after.time_played;
-                                    if b == a { DiffParam::Same }
-                                    else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
-                                    else { DiffParam::Outlier(format!("+{}", a - b)) }
-                                }
-                                else if !bsr && !asr {
-                                    let b = before.time_played;
-                                    let a = after.time_played;
-                                    if b == a { DiffParam::Same }
-                                    else if b < a { DiffParam::Normal(format!("+{}", a - b)) }
-                                    else { DiffParam::Outlier(format!("-{}", b - a)) }
-                                }
-                                else if !bsr && asr {
-                                    DiffParam::Normal("new".to_string())
-                                }
-                                else {
-                                    DiffParam::Outlier("degrate".to_string())
-                                }
-                            }
-                            else if !bsr && asr {
-                                DiffParam::Normal("new".to_string())
-                            }
-                            else {
-                                DiffParam::Outlier("degrate".to_string())
-                            }
-                        };
-                        let fc = {
-                            let bsr = before.full_clear;
-                            let asr = after.full_clear;
-                            if bsr && asr {
-                                let b = before.best_full_clear_time;
-                                let a = after.best_full_clear_time;
+                                let b = before.time_played;
+                                let a = after.time_played;
                                 if b == a { DiffParam::Same }
-                                else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
-                                else { DiffParam::Outlier(format!("+{}", a - b)) }
-                            }
-                            else if !bsr && !asr {
-                                DiffParam::Same
+                                else if b < a { DiffParam::Normal(format!("+{}", a - b)) }
+                                else { DiffParam::Outlier(format!("-{}", b - a)) }
                             }
                             else if !bsr && asr {
                                 DiffParam::Normal("new".to_string())
@@ -125,13 +111,39 @@ impl SaveDataDiff {
                             else {
                                 DiffParam::Outlier("degrate".to_string())
                             }
-                        };
-                        StatsDiff::Diff { strawberries, best_deaths, deaths, clr, fc }.same_check()
-                    }
-                };
-                if stats_diff != StatsDiff::Same {
-                    diff.stats_diffs.push((map_data, stats_diff));
+                        }
+                        else if !bsr && asr {
+                            DiffParam::Normal("new".to_string())
+                        }
+                        else {
+                            DiffParam::Outlier("degrate".to_string())
+                        }
+                    };
+                    let fc = {
+                        let bsr = before.full_clear;
+                        let asr = after.full_clear;
+                        if bsr && asr {
+                            let b = before.best_full_clear_time;
+                            let a = after.best_full_clear_time;
+                            if b == a { DiffParam::Same }
+                            else if b > a { DiffParam::Normal(format!("-{}", b - a)) }
+                            else { DiffParam::Outlier(format!("+{}", a - b)) }
+                        }
+                        else if !bsr && !asr {
+                            DiffParam::Same
+                        }
+                        else if !bsr && asr {
+                            DiffParam::Normal("new".to_string())
+                        }
+                        else {
+                            DiffParam::Outlier("degrate".to_string())
+                        }
+                    };
+                    StatsDiff::Diff { strawberries, best_deaths, deaths, clr, fc }.same_check()
                 }
+            };
+            if stats_diff != StatsDiff::Same {
+                diff.stats_diffs.push((map_data, stats_diff));
             }
         }
         diff
@@ -202,6 +214,19 @@ impl DiffParam {
     }
 }
 
+impl DiffParam {
+    // show two changes of the same column in one cell
+    pub fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (DiffParam::Same, p) | (p, DiffParam::Same) => p,
+            (DiffParam::Normal(a), DiffParam::Normal(b)) => DiffParam::Normal(format!("{} {}", a, b)),
+            (DiffParam::Normal(a), DiffParam::Outlier(b))
+                | (DiffParam::Outlier(a), DiffParam::Normal(b))
+                | (DiffParam::Outlier(a), DiffParam::Outlier(b)) => DiffParam::Outlier(format!("{} {}", a, b)),
+        }
+    }
+}
+
 impl Default for DiffParam {
     fn default() -> Self {
         DiffParam::Same
@@ -222,14 +247,20 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
 {
     let diff = SaveDataDiff::create_diff(game_data, before, after);
     let map_num = diff.stats_diffs.len();
+    // a run in progress changes nothing in the stats, so show it under the diff
+    let all_maps = ProgressView::new(game_data, after).maps().collect::<Vec<_>>();
+    let session = crate::session_text(after, all_maps.iter(), lang);
 
     let margin = 30;
     let row_height = 40;
     let font_size = 25;
-    let col_widths = vec![350, 80, 80, 80, 220, 220];
+    // SB is wider when some maps show "collected/total"
+    let sb_width = if diff.stats_diffs.iter().any(|(map_data, _)| map_data.berry_total().is_some()) { 120 } else { 80 };
+    let col_widths = vec![350, sb_width, 80, 80, 220, 220];
     let col_acc = col_widths.iter().fold(vec![0], |mut v, e| { v.push(v[v.len() - 1] + e); v });
     let chart_width = col_acc[col_acc.len() - 1];
-    let chart_height = row_height * (map_num as i64 * 2 + 1);
+    let footer_height = if session.is_some() { row_height } else { 0 };
+    let chart_height = row_height * (map_num as i64 * 2 + 1) + footer_height;
     let mut chart = Chart::new(-margin, -margin, chart_width + margin * 2, chart_height + margin * 2i64);
     {
         let bg = Path::new()
@@ -278,7 +309,8 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
         for (k, sd) in [before, after].into_iter().enumerate() {
             match sd.map_stats.get(&code) {
                 None => {
-                    let elems = vec![map_data.try_local_name(lang), "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()];
+                    let sb = map_data.berry_total().map(|total| format!("0/{}", total)).unwrap_or_else(|| "-".to_string());
+                    let elems = vec![map_data.try_local_name(lang), sb, "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()];
                     for (j, text) in elems.into_iter().enumerate() {
                         if j == 0 {
                         }
@@ -290,12 +322,38 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
                 Some(stats) => {
                     let ch_text = centered_text_box(&map_data.try_local_name(lang)).text_anchor(text_anchor::TextAnchorValue::Start);
 
-                    let sb_text = centered_text_box(&stats.total_strawberries().to_string());
+                    // records from assisted files are grayed out instead of gold
+                    let best_color = || if stats.assisted { color255(206.0, 212.0, 218.0) } else { color255(255.0, 236.0, 163.0) };
+                    let completed_color = || if stats.assisted { color255(173.0, 181.0, 189.0) } else { color255(252.0, 195.0, 50.0) };
+
+                    let berries = map_data.count_berries(stats);
+                    let berry_total = map_data.berry_total();
+                    // goldens are not counted in SB, they turn the cell gold instead
+                    let sb_bg_color =
+                        if berries.goldens() > 0 {
+                            completed_color()
+                        }
+                        else if berry_total.map(|total| berries.strawberries() >= total).unwrap_or(false) {
+                            best_color()
+                        }
+                        else {
+                            color255(255.0, 255.0, 255.0)
+                        };
+                    let sb_bg = Path::new()
+                        .line_rel(col_widths[1], 0)
+                        .line_rel(0, row_height)
+                        .line_rel(-col_widths[1], 0)
+                        .close()
+                        .fill(sb_bg_color);
+                    let sb_text = match berry_total {
+                        Some(total) => centered_text_box(&format!("{}/{}", berries.strawberries(), total)),
+                        None => centered_text_box(&berries.strawberries().to_string()),
+                    };
 
                     let best_str = if stats.single_run_completed { stats.best_deaths.to_string() } else { "-".to_string() };
                     let best_bg_color = 
                         if best_str == "0" {
-                            color255(255.0, 236.0, 163.0)
+                            best_color()
                         }
                         else {
                             color255(255.0, 255.0, 255.0)
@@ -316,10 +374,10 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
                         else { format!("({})", stats.time_played.to_string()) };
                     let clr_bg_color = 
                         if stats.single_run_completed {
-                            color255(255.0, 236.0, 163.0)
+                            best_color()
                         }
                         else if stats.completed {
-                            color255(252.0, 195.0, 50.0)
+                            completed_color()
                         }
                         else {
                             color255(255.0, 255.0, 255.0)
@@ -337,7 +395,7 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
                         else { "-".to_string() };
                     let fc_bg_color = 
                         if stats.full_clear {
-                            color255(255.0, 236.0, 163.0)
+                            best_color()
                         }
                         else {
                             color255(255.0, 255.0, 255.0)
@@ -352,6 +410,7 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
                     let row_start = row_height * (i as i64 * 2 + k as i64 + 1);
                     let row_center = row_height * (i as i64 * 2 + k as i64 + 1) + row_height / 2;
                     chart = chart
+                        .draw(sb_bg, col_acc[1], row_start)
                         .draw(sb_text, col_acc[1] + col_widths[1] / 2, row_center)
                         .draw(best_bg, col_acc[2], row_start)
                         .draw(best_text, col_acc[2] + col_widths[2] / 2, row_center)
@@ -359,7 +418,10 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
                         .draw(clr_bg, col_acc[4], row_start)
                         .draw(clr_text, col_acc[4] + col_widths[4] / 2, row_center)
                         .draw(fc_bg, col_acc[5], row_start)
-                        .draw(fc_text, col_acc[5] + col_widths[5] / 2, row_center)
+                        .draw(fc_text, col_acc[5] + col_widths[5] / 2, row_center);
+                    if let Some(cp_text) = crate::checkpoint_text(&map_data, stats) {
+                        chart = chart.draw(cp_text, col_acc[5] - 3, row_start + 7);
+                    }
                 }
             }
         }
@@ -442,6 +504,10 @@ pub fn diff_svg_chart(game_data: &GameData, before: &SaveData, after: &SaveData,
             }
         }
     }
+    if let Some(session) = session {
+        let text = centered_text_box(&session).text_anchor(text_anchor::TextAnchorValue::Start);
+        chart = chart.draw(text, 0, row_height * (map_num as i64 * 2 + 1) + row_height / 2);
+    }
 
     (chart, chart_width, chart_height)
 }
@@ -4,25 +4,112 @@ use svg_vis::chart::Chart;
 use svg_vis::attribute::*;
 use svg_vis::literal::Color;
 use svg_vis::element::{ Text, Path };
-use celeste_save_data_rs::save_data::SaveData;
+use celeste_save_data_rs::save_data::{ SaveData, AreaModeStats };
 use celeste_save_data_rs::map_data::MapData;
 use resvg::usvg::{ fontdb, Tree, TreeParsing, TreeTextToPath };
 use resvg::tiny_skia::Pixmap;
 use resvg::render;
 
+// footer line for a run that was in progress when the file was saved
+pub(crate) fn session_text<'a, MI>(save_data: &SaveData, maps: MI, lang: &str) -> Option<String>
+    where MI: IntoIterator<Item=&'a MapData>,
+{
+    let session = save_data.current_session.as_ref()?;
+    let code = session.code();
+    // only the chart that has the map gets the footer
+    let name = maps.into_iter()
+        .find(|map_data| map_data.code == code)?
+        .try_local_name(lang);
+    Some(format!("currently playing: {} [{}] {} deaths, {}", name, session.level, session.deaths, session.time))
+}
+
+// "CP2" at the corner of the CLR cell, for the furthest checkpoint of an uncleared map
+pub(crate) fn checkpoint_text(map_data: &MapData, stats: &AreaModeStats) -> Option<Text> {
+    if stats.completed {
+        return None;
+    }
+    let checkpoint = map_data.furthest_checkpoint(stats)?;
+    Some(Text::new()
+        .set_text(&format!("CP{}", checkpoint))
+        .font_size(12)
+        .text_anchor(text_anchor::TextAnchorValue::End)
+        .dominant_baseline(dominant_baseline::DominantBaselineValue::Central))
+}
+
+const CREDITS_MAX_WIDTH: i64 = 300;
+
+// rough width of a character in 12px text, wide characters take about twice as much
+fn small_char_width(c: char) -> i64 {
+    if c.is_ascii() { 7 } else { 12 }
+}
+
+fn small_text_width(s: &str) -> i64 {
+    s.chars().map(small_char_width).sum()
+}
+
+// cut s to fit in width, ending with "…" when it is cut
+fn truncate_small_text(s: &str, width: i64) -> String {
+    if small_text_width(s) <= width {
+        return s.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = small_char_width('…');
+    for c in s.chars() {
+        used += small_char_width(c);
+        if used > width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push('…');
+    truncated
+}
+
 fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Chart, i64, i64)
     where MI: IntoIterator<Item=MapData>,
           MI::IntoIter: ExactSizeIterator,
 {
-    let map_iter = map_iter.into_iter();
-    let map_num = map_iter.len();
+    let maps = map_iter.into_iter().collect::<Vec<_>>();
+    let map_num = maps.len();
     let margin = 30;
     let row_height = 30;
     let font_size = 25;
-    let col_widths = vec![350, 80, 80, 80, 220, 220];
+    // the cassette column only shows up when one of these maps has its cassette collected
+    let has_cassette = |map_data: &MapData| {
+        save_data.area_info.get(&map_data.code.sid).map(|info| info.cassette).unwrap_or(false)
+    };
+    let show_cassette = maps.iter().any(has_cassette);
+    let session = session_text(save_data, maps.iter(), lang);
+    let footer_height = if session.is_some() { row_height } else { 0 };
+    // SB is wider when some maps show "collected/total"
+    let sb_width = if maps.iter().any(|map_data| map_data.berry_total().is_some()) { 120 } else { 80 };
+    let mut col_widths = vec![350, sb_width, 80, 80, 220, 220];
+    let mut headers = vec!["chapter", "SB", "best", "deaths", "CLR", "FC"];
+    if show_cassette {
+        col_widths.push(80);
+        headers.push("CS");
+    }
+    // goldens are not counted in SB, they get a column when one of these maps has one
+    let has_goldens = |map_data: &MapData| {
+        save_data.map_stats.get(&map_data.code).map(|stats| map_data.count_berries(stats).goldens() > 0).unwrap_or(false)
+    };
+    let golden_col = maps.iter().any(has_goldens).then(|| {
+        col_widths.push(80);
+        headers.push("G");
+        col_widths.len() - 1
+    });
+    // credits get their own column at the end so that long names never run into them
+    let credits = maps.iter()
+        .map(|map_data| map_data.credits().map(|credits| truncate_small_text(&credits, CREDITS_MAX_WIDTH)))
+        .collect::<Vec<_>>();
+    let credits_col = credits.iter().flatten().map(|credits| small_text_width(credits)).max().map(|width| {
+        col_widths.push(std::cmp::max(width + 10, 60));
+        headers.push("by");
+        col_widths.len() - 1
+    });
     let col_acc = col_widths.iter().fold(vec![0], |mut v, e| { v.push(v[v.len() - 1] + e); v });
     let chart_width = col_acc[col_acc.len() - 1];
-    let chart_height = row_height * (map_num as i64 + 1);
+    let chart_height = row_height * (map_num as i64 + 1) + footer_height;
     let mut chart = Chart::new(-margin, -margin, chart_width + margin * 2, chart_height + margin * 2i64);
     {
         let bg = Path::new()
@@ -42,7 +129,7 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
             .dominant_baseline(dominant_baseline::DominantBaselineValue::Central)
     };
     {
-        for (i, text) in ["chapter", "SB", "best", "deaths", "CLR", "FC"].into_iter().enumerate() {
+        for (i, text) in headers.into_iter().enumerate() {
             chart = chart
                 .draw(centered_text_box(text), col_acc[i] + col_widths[i] / 2, row_height / 2);
         }
@@ -52,11 +139,21 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
         Color::from_rgb(r/255.0, g/255.0, b/255.0)
     };
 
-    for (i, map_data) in map_iter.enumerate() {
+    for (i, map_data) in maps.into_iter().enumerate() {
+        if let (Some(credits), Some(col)) = (&credits[i], credits_col) {
+            let credits_text = Text::new()
+                .set_text(credits)
+                .font_size(12)
+                .text_anchor(text_anchor::TextAnchorValue::Start)
+                .dominant_baseline(dominant_baseline::DominantBaselineValue::Central);
+            chart = chart.draw(credits_text, col_acc[col] + 5, row_height * (i as i64 + 1) + row_height / 2);
+        }
         let MapData { code, .. } = &map_data;
         match save_data.map_stats.get(&code) {
             None => {
-               let elems = vec![map_data.try_local_name(lang), "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()];
+               let sb = map_data.berry_total().map(|total| format!("0/{}", total)).unwrap_or_else(|| "-".to_string());
+               let mut elems = vec![map_data.try_local_name(lang), sb, "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()];
+               elems.resize(credits_col.unwrap_or(col_widths.len()), "-".to_string());
                for (j, text) in elems.into_iter().enumerate() {
                    if j == 0 {
                        chart = chart.draw(centered_text_box(&text).text_anchor(text_anchor::TextAnchorValue::Start), col_acc[j], row_height / 2 + row_height * (i as i64 + 1));
@@ -69,12 +166,34 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
             Some(stats) => {
                 let ch_text = centered_text_box(&map_data.try_local_name(lang)).text_anchor(text_anchor::TextAnchorValue::Start);
 
-                let sb_text = centered_text_box(&stats.total_strawberries().to_string());
+                // records from assisted files are grayed out instead of gold
+                let best_color = || if stats.assisted { color255(206.0, 212.0, 218.0) } else { color255(255.0, 236.0, 163.0) };
+                let completed_color = || if stats.assisted { color255(173.0, 181.0, 189.0) } else { color255(252.0, 195.0, 50.0) };
+
+                let berries = map_data.count_berries(stats);
+                let berry_total = map_data.berry_total();
+                let sb_bg_color =
+                    if berry_total.map(|total| berries.strawberries() >= total).unwrap_or(false) {
+                        best_color()
+                    }
+                    else {
+                        color255(255.0, 255.0, 255.0)
+                    };
+                let sb_bg = Path::new()
+                    .line_rel(col_widths[1], 0)
+                    .line_rel(0, row_height)
+                    .line_rel(-col_widths[1], 0)
+                    .close()
+                    .fill(sb_bg_color);
+                let sb_text = match berry_total {
+                    Some(total) => centered_text_box(&format!("{}/{}", berries.strawberries(), total)),
+                    None => centered_text_box(&berries.strawberries().to_string()),
+                };
 
                 let best_str = if stats.single_run_completed { stats.best_deaths.to_string() } else { "-".to_string() };
                 let best_bg_color = 
                     if best_str == "0" {
-                        color255(255.0, 236.0, 163.0)
+                        best_color()
                     }
                     else {
                         color255(255.0, 255.0, 255.0)
@@ -95,10 +214,10 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
                     else { format!("({})", stats.time_played.to_string()) };
                 let clr_bg_color = 
                     if stats.single_run_completed {
-                        color255(255.0, 236.0, 163.0)
+                        best_color()
                     }
                     else if stats.completed {
-                        color255(252.0, 195.0, 50.0)
+                        completed_color()
                     }
                     else {
                         color255(255.0, 255.0, 255.0)
@@ -116,7 +235,7 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
                     else { "-".to_string() };
                 let fc_bg_color = 
                     if stats.full_clear {
-                        color255(255.0, 236.0, 163.0)
+                        best_color()
                     }
                     else {
                         color255(255.0, 255.0, 255.0)
@@ -132,6 +251,7 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
                 let row_center = row_height * (i as i64 + 1) + row_height / 2;
                 chart = chart
                     .draw(ch_text, col_acc[0], row_center)
+                    .draw(sb_bg, col_acc[1], row_start)
                     .draw(sb_text, col_acc[1] + col_widths[1] / 2, row_center)
                     .draw(best_bg, col_acc[2], row_start)
                     .draw(best_text, col_acc[2] + col_widths[2] / 2, row_center)
@@ -139,7 +259,38 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
                     .draw(clr_bg, col_acc[4], row_start)
                     .draw(clr_text, col_acc[4] + col_widths[4] / 2, row_center)
                     .draw(fc_bg, col_acc[5], row_start)
-                    .draw(fc_text, col_acc[5] + col_widths[5] / 2, row_center)
+                    .draw(fc_text, col_acc[5] + col_widths[5] / 2, row_center);
+                // show how far an uncleared map has been played
+                if let Some(cp_text) = checkpoint_text(&map_data, stats) {
+                    chart = chart.draw(cp_text, col_acc[5] - 3, row_start + 7);
+                }
+                if show_cassette {
+                    // the cassette belongs to the whole area, so only mark it on the first side
+                    let collected = code.side == 0 && has_cassette(&map_data);
+                    let cs_bg = Path::new()
+                        .line_rel(col_widths[6], 0)
+                        .line_rel(0, row_height)
+                        .line_rel(-col_widths[6], 0)
+                        .close()
+                        .fill(if collected { best_color() } else { color255(255.0, 255.0, 255.0) });
+                    let cs_text = centered_text_box(if collected { "○" } else { "-" });
+                    chart = chart
+                        .draw(cs_bg, col_acc[6], row_start)
+                        .draw(cs_text, col_acc[6] + col_widths[6] / 2, row_center);
+                }
+                if let Some(col) = golden_col {
+                    let goldens = berries.goldens();
+                    let g_bg = Path::new()
+                        .line_rel(col_widths[col], 0)
+                        .line_rel(0, row_height)
+                        .line_rel(-col_widths[col], 0)
+                        .close()
+                        .fill(if goldens > 0 { completed_color() } else { color255(255.0, 255.0, 255.0) });
+                    let g_text = centered_text_box(&if goldens > 0 { goldens.to_string() } else { "-".to_string() });
+                    chart = chart
+                        .draw(g_bg, col_acc[col], row_start)
+                        .draw(g_text, col_acc[col] + col_widths[col] / 2, row_center);
+                }
             }
         }
     }
@@ -152,6 +303,10 @@ fn generate_svg_chart<MI>(save_data: &SaveData, map_iter: MI, lang: &str) -> (Ch
             .stroke(color);
         chart = chart.draw(path, 0, row_height * (i as i64 + 1))
     }
+    if let Some(session) = session {
+        let text = centered_text_box(&session).text_anchor(text_anchor::TextAnchorValue::Start);
+        chart = chart.draw(text, 0, row_height * (map_num as i64 + 1) + row_height / 2);
+    }
     (chart, chart_width, chart_height)
 }
 